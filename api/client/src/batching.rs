@@ -0,0 +1,206 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use error_stack::Report;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::AppendLogResponse;
+use morax_protos::request::Entry;
+use tokio::sync::oneshot;
+
+use crate::ClientError;
+use crate::HTTPClient;
+use crate::HTTPResponse;
+
+type AppendResult = error_stack::Result<HTTPResponse<AppendLogResponse>, ClientError>;
+
+/// Coalesces the concurrent appends to the same log into a single append request.
+///
+/// An append waits up to the linger time for other appends to the same log, and the batch is sent
+/// once the linger time elapses or it reaches the maximum entries. Each append gets back the
+/// offsets of its own entries; if the batch fails, all the appends in it get the same failure,
+/// while the other batches are not affected.
+///
+/// Appends with an expected offset are sent as is, since they cannot share a batch.
+#[derive(Debug, Clone)]
+pub struct BatchingClient {
+    client: Arc<HTTPClient>,
+    linger: Duration,
+    max_batch_entries: usize,
+    state: Arc<Mutex<BatchState>>,
+}
+
+#[derive(Debug, Default)]
+struct BatchState {
+    next_batch_id: u64,
+    // log name -> the batch open for appends
+    batches: HashMap<String, Batch>,
+}
+
+#[derive(Debug)]
+struct Batch {
+    id: u64,
+    entries: Vec<Entry>,
+    // the number of entries of each append, in order, and where to send back its result
+    appends: Vec<(usize, oneshot::Sender<AppendResult>)>,
+}
+
+impl BatchingClient {
+    /// Creates a batching client over `client` that lingers 5 milliseconds for at most 1000
+    /// entries per batch.
+    pub fn new(client: HTTPClient) -> Self {
+        Self {
+            client: Arc::new(client),
+            linger: Duration::from_millis(5),
+            max_batch_entries: 1000,
+            state: Arc::new(Mutex::new(BatchState::default())),
+        }
+    }
+
+    /// How long an append waits for other appends to the same log before the batch is sent.
+    pub fn with_linger(mut self, linger: Duration) -> Self {
+        self.linger = linger;
+        self
+    }
+
+    /// The maximum number of entries in a batch; it should not exceed the `max_append_entries` of
+    /// the broker. An append with more entries is sent in a batch of its own.
+    pub fn with_max_batch_entries(mut self, max_batch_entries: usize) -> Self {
+        self.max_batch_entries = max_batch_entries;
+        self
+    }
+
+    /// The client to send the requests other than appends.
+    pub fn client(&self) -> &HTTPClient {
+        &self.client
+    }
+
+    pub async fn append_log(&self, request: AppendLogRequest) -> AppendResult {
+        if request.expected_offset.is_some() || request.entries.is_empty() {
+            return self.client.append_log(request).await;
+        }
+
+        let name = request.name;
+        let (tx, rx) = oneshot::channel();
+        let mut full_batches = vec![];
+        let mut new_batch_id = None;
+        {
+            let mut state = self.state.lock().unwrap();
+            let BatchState {
+                next_batch_id,
+                batches,
+            } = &mut *state;
+
+            // a batch that cannot take the entries is sent as is, and the entries start a new one
+            if let Some(batch) = batches.get(&name) {
+                if batch.entries.len() + request.entries.len() > self.max_batch_entries {
+                    full_batches.extend(batches.remove(&name));
+                }
+            }
+
+            let batch = batches.entry(name.clone()).or_insert_with(|| {
+                let id = *next_batch_id;
+                *next_batch_id += 1;
+                new_batch_id = Some(id);
+                Batch {
+                    id,
+                    entries: vec![],
+                    appends: vec![],
+                }
+            });
+            batch.appends.push((request.entries.len(), tx));
+            batch.entries.extend(request.entries);
+            if batch.entries.len() >= self.max_batch_entries {
+                full_batches.extend(batches.remove(&name));
+            }
+        }
+
+        for batch in full_batches {
+            tokio::spawn(self.clone().send(name.clone(), batch));
+        }
+        if let Some(batch_id) = new_batch_id {
+            let this = self.clone();
+            let name = name.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(this.linger).await;
+                this.flush(name, batch_id).await;
+            });
+        }
+
+        rx.await.unwrap_or_else(|_| {
+            Err(Report::new(ClientError(format!(
+                "append batch to log {name} is dropped"
+            ))))
+        })
+    }
+
+    /// Sends the batch of `batch_id` if it has not been sent for being full.
+    async fn flush(self, name: String, batch_id: u64) {
+        let batch = {
+            let mut state = self.state.lock().unwrap();
+            match state.batches.get(&name) {
+                Some(batch) if batch.id == batch_id => state.batches.remove(&name),
+                _ => None,
+            }
+        };
+        if let Some(batch) = batch {
+            self.send(name, batch).await;
+        }
+    }
+
+    async fn send(self, name: String, batch: Batch) {
+        let entry_cnt = batch.entries.len();
+        let request = AppendLogRequest {
+            name: name.clone(),
+            entries: batch.entries,
+            expected_offset: None,
+        };
+        let result = self.client.append_log(request).await;
+
+        match result {
+            Ok(HTTPResponse::Success(response)) => {
+                // the entries of a batch are appended in order into a single split
+                let mut start = response.offsets.start;
+                for (len, tx) in batch.appends {
+                    let end = start + len as i64;
+                    let response = AppendLogResponse {
+                        offsets: start..end,
+                    };
+                    let _ = tx.send(Ok(HTTPResponse::Success(response)));
+                    start = end;
+                }
+            }
+            Ok(response) => {
+                for (_, tx) in batch.appends {
+                    let _ = tx.send(Ok(response.clone()));
+                }
+            }
+            Err(err) => {
+                // reports are not cloneable, so each append gets a copy of the error printed
+                let message =
+                    format!("failed to append batch of {entry_cnt} entries to log {name}");
+                let cause = format!("{err:?}");
+                for (_, tx) in batch.appends {
+                    let err =
+                        Report::new(ClientError(message.clone())).attach_printable(cause.clone());
+                    let _ = tx.send(Err(err));
+                }
+            }
+        }
+    }
+}
@@ -13,25 +13,43 @@
 // limitations under the License.
 
 use backon::BackoffBuilder;
+use backon::ExponentialBuilder;
 use backon::Retryable;
 use error_stack::ResultExt;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use morax_protos::request::AppendLogRequest;
 use morax_protos::request::AppendLogResponse;
 use morax_protos::request::CreateLogRequest;
 use morax_protos::request::CreateLogResponse;
+use morax_protos::request::DeleteLogRequest;
+use morax_protos::request::DeleteLogResponse;
+use morax_protos::request::DescribeSplitResponse;
+use morax_protos::request::Entry;
 use morax_protos::request::ErrorResponse;
+use morax_protos::request::FollowLogRequest;
 use morax_protos::request::ReadLogRequest;
 use morax_protos::request::ReadLogResponse;
+use morax_protos::request::SealLogResponse;
+use morax_protos::request::TrimLogRequest;
+use morax_protos::request::TrimLogResponse;
 use reqwest::Client;
 use reqwest::ClientBuilder;
 use reqwest::Response;
 use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+mod batching;
+pub use batching::BatchingClient;
 
 #[derive(Debug, thiserror::Error)]
 #[error("{0}")]
 pub struct ClientError(String);
 
+/// The entries streamed by [`HTTPClient::follow_log`].
+pub type EntryStream = BoxStream<'static, error_stack::Result<Entry, ClientError>>;
+
 #[derive(Debug, Clone)]
 pub enum HTTPResponse<T> {
     Success(T),
@@ -65,6 +83,8 @@ impl std::fmt::Display for ErrorStatus {
 pub struct HTTPClient {
     endpoint: String,
     client: Client,
+    retry: Option<ExponentialBuilder>,
+    retry_appends: bool,
 }
 
 impl HTTPClient {
@@ -78,9 +98,32 @@ impl HTTPClient {
         Ok(Self {
             endpoint: endpoint.clone(),
             client: builder.build().change_context_lazy(make_error)?,
+            retry: None,
+            retry_appends: false,
         })
     }
 
+    /// Retries requests that fail to connect, time out, or get a 502, 503 or 504 response, with
+    /// the given backoff.
+    ///
+    /// Only requests that are safe to repeat are retried, i.e., trimming and reading logs. Creating
+    /// and deleting logs are not retried, since a repeated request fails if the previous attempt
+    /// succeeded but its response was lost. See [`HTTPClient::with_append_retry`] for retrying
+    /// appends.
+    ///
+    /// Once the retries are exhausted, the last response is returned as a failure.
+    pub fn with_retry(mut self, backoff: ExponentialBuilder) -> Self {
+        self.retry = Some(backoff);
+        self
+    }
+
+    /// Whether to retry appends as well. A retried append may append the same entries twice if
+    /// the previous attempt succeeded but its response was lost.
+    pub fn with_append_retry(mut self, retry_appends: bool) -> Self {
+        self.retry_appends = retry_appends;
+        self
+    }
+
     pub async fn health_check<B: BackoffBuilder>(
         &self,
         backoff: Option<B>,
@@ -106,6 +149,21 @@ impl HTTPClient {
         Ok(())
     }
 
+    /// Checks whether the broker is ready to serve requests, i.e., its dependencies are reachable.
+    pub async fn ready_check(&self) -> error_stack::Result<(), ClientError> {
+        let url = format!("{}/v1/ready", self.endpoint);
+        let make_error = || ClientError(format!("failed to ready check: {url:?}"));
+
+        self.client
+            .get(&url)
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .change_context_lazy(make_error)?;
+
+        Ok(())
+    }
+
     pub async fn create_log(
         &self,
         request: CreateLogRequest,
@@ -113,10 +171,39 @@ impl HTTPClient {
         let make_error = || ClientError(format!("failed to create log: {request:?}"));
 
         let response = self
-            .client
-            .post(format!("{}/v1/create", self.endpoint))
-            .json(&request)
-            .send()
+            .send("create", &request, false)
+            .await
+            .change_context_lazy(make_error)?;
+
+        make_response(response).await
+    }
+
+    pub async fn delete_log(
+        &self,
+        request: DeleteLogRequest,
+    ) -> error_stack::Result<HTTPResponse<DeleteLogResponse>, ClientError> {
+        let make_error = || ClientError(format!("failed to delete log: {request:?}"));
+
+        let response = self
+            .send("delete", &request, false)
+            .await
+            .change_context_lazy(make_error)?;
+
+        make_response(response).await
+    }
+
+    /// Trims the log so that the entries below `request.offset` are no longer readable; reading
+    /// below the start offset returned fails with [`ErrorCode::OutOfRange`].
+    ///
+    /// [`ErrorCode::OutOfRange`]: morax_protos::request::ErrorCode::OutOfRange
+    pub async fn trim_log(
+        &self,
+        request: TrimLogRequest,
+    ) -> error_stack::Result<HTTPResponse<TrimLogResponse>, ClientError> {
+        let make_error = || ClientError(format!("failed to trim log: {request:?}"));
+
+        let response = self
+            .send("trim", &request, true)
             .await
             .change_context_lazy(make_error)?;
 
@@ -130,10 +217,7 @@ impl HTTPClient {
         let make_error = || ClientError(format!("failed to append log: {request:?}"));
 
         let response = self
-            .client
-            .post(format!("{}/v1/append", self.endpoint))
-            .json(&request)
-            .send()
+            .send("append", &request, self.retry_appends)
             .await
             .change_context_lazy(make_error)?;
 
@@ -146,16 +230,167 @@ impl HTTPClient {
     ) -> error_stack::Result<HTTPResponse<ReadLogResponse>, ClientError> {
         let make_error = || ClientError(format!("failed to read log: {request:?}"));
 
+        let response = self
+            .send("read", &request, true)
+            .await
+            .change_context_lazy(make_error)?;
+
+        make_response(response).await
+    }
+
+    /// Reads the split of `split_id` as is and summarizes it; useful for debugging a split
+    /// suspected to be corrupt.
+    pub async fn describe_split(
+        &self,
+        split_id: &str,
+    ) -> error_stack::Result<HTTPResponse<DescribeSplitResponse>, ClientError> {
+        let url = format!("{}/v1/admin/splits/{split_id}", self.endpoint);
+        let make_error = || ClientError(format!("failed to describe split: {url:?}"));
+
         let response = self
             .client
-            .post(format!("{}/v1/read", self.endpoint))
-            .json(&request)
+            .get(&url)
             .send()
             .await
             .change_context_lazy(make_error)?;
 
         make_response(response).await
     }
+
+    /// Seals the log so that appends fail with [`ErrorCode::Sealed`] while reads keep working.
+    ///
+    /// [`ErrorCode::Sealed`]: morax_protos::request::ErrorCode::Sealed
+    pub async fn seal_log(
+        &self,
+        name: &str,
+    ) -> error_stack::Result<HTTPResponse<SealLogResponse>, ClientError> {
+        self.post_admin_log(name, "seal").await
+    }
+
+    /// Unseals the log sealed by [`HTTPClient::seal_log`], so that it accepts appends again.
+    pub async fn unseal_log(
+        &self,
+        name: &str,
+    ) -> error_stack::Result<HTTPResponse<SealLogResponse>, ClientError> {
+        self.post_admin_log(name, "unseal").await
+    }
+
+    /// Follows the log from `request.offset`; the returned stream yields the existing entries and
+    /// then the newly appended ones, and ends when the follow timeout elapses.
+    pub async fn follow_log(
+        &self,
+        request: FollowLogRequest,
+    ) -> error_stack::Result<HTTPResponse<EntryStream>, ClientError> {
+        let make_error = || ClientError(format!("failed to follow log: {request:?}"));
+
+        let response = self
+            .send("follow", &request, true)
+            .await
+            .change_context_lazy(make_error)?;
+        if !response.status().is_success() {
+            return make_failure(response).await;
+        }
+
+        let state = (Box::pin(response.bytes_stream()), Vec::<u8>::new());
+        let entries = futures::stream::unfold(Some(state), |state| async move {
+            let make_error = || ClientError("failed to read followed entries".to_string());
+
+            let (mut body, mut buf) = state?;
+            loop {
+                if let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                    let line = buf.drain(..=pos).collect::<Vec<_>>();
+                    let entry = serde_json::from_slice::<Entry>(&line[..pos])
+                        .change_context_lazy(make_error);
+                    return Some((entry, Some((body, buf))));
+                }
+                match body.next().await? {
+                    Ok(chunk) => buf.extend_from_slice(&chunk),
+                    Err(err) => {
+                        let err = Err::<Entry, _>(err).change_context_lazy(make_error);
+                        return Some((err, None));
+                    }
+                }
+            }
+        });
+        Ok(HTTPResponse::Success(entries.boxed()))
+    }
+}
+
+impl HTTPClient {
+    async fn post_admin_log<T: DeserializeOwned>(
+        &self,
+        name: &str,
+        action: &str,
+    ) -> error_stack::Result<HTTPResponse<T>, ClientError> {
+        let url = format!("{}/v1/admin/logs/{name}/{action}", self.endpoint);
+        let make_error = || ClientError(format!("failed to {action} log: {url:?}"));
+
+        let response = self
+            .client
+            .post(&url)
+            .send()
+            .await
+            .change_context_lazy(make_error)?;
+
+        make_response(response).await
+    }
+
+    async fn send(
+        &self,
+        path: &str,
+        request: &impl Serialize,
+        retryable: bool,
+    ) -> Result<Response, reqwest::Error> {
+        let url = format!("{}/v1/{path}", self.endpoint);
+
+        let Some(backoff) = self.retry.filter(|_| retryable) else {
+            return self.client.post(&url).json(request).send().await;
+        };
+
+        let send = || async {
+            let response = self
+                .client
+                .post(&url)
+                .json(request)
+                .send()
+                .await
+                .map_err(SendError::Request)?;
+            if is_retryable_status(response.status()) {
+                Err(SendError::Status(response))
+            } else {
+                Ok(response)
+            }
+        };
+        let sent = send
+            .retry(backoff)
+            .when(|err| match err {
+                SendError::Request(err) => err.is_connect() || err.is_timeout(),
+                SendError::Status(_) => true,
+            })
+            .await;
+        match sent {
+            Ok(response) => Ok(response),
+            // the last response is returned as is, so that its error payload is kept
+            Err(SendError::Status(response)) => Ok(response),
+            Err(SendError::Request(err)) => Err(err),
+        }
+    }
+}
+
+/// Why an attempt to send a request is retried.
+#[derive(Debug)]
+enum SendError {
+    /// The request fails to be sent, or its response fails to be received.
+    Request(reqwest::Error),
+    /// The response has a retryable status.
+    Status(Response),
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
 }
 
 async fn make_response<T: DeserializeOwned>(
@@ -163,13 +398,18 @@ async fn make_response<T: DeserializeOwned>(
 ) -> error_stack::Result<HTTPResponse<T>, ClientError> {
     let make_error = || ClientError("failed to make response".to_string());
 
-    let status = r.status();
-
-    if status.is_success() {
+    if r.status().is_success() {
         let result = r.json().await.change_context_lazy(make_error)?;
         return Ok(HTTPResponse::Success(result));
     }
 
+    make_failure(r).await
+}
+
+async fn make_failure<T>(r: Response) -> error_stack::Result<HTTPResponse<T>, ClientError> {
+    let make_error = || ClientError("failed to make response".to_string());
+
+    let status = r.status();
     let payload = r.bytes().await.change_context_lazy(make_error)?;
     if let Ok(resp) = serde_json::from_slice::<ErrorResponse>(&payload) {
         return Ok(HTTPResponse::Failure(resp));
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroUsize;
+
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -20,4 +22,27 @@ pub struct BrokerConfig {
     pub listen_addr: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub advertise_addr: Option<String>,
+    /// The maximum number of entries in a single append request; defaults to 10000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_append_entries: Option<NonZeroUsize>,
+    /// The maximum number of requests served at the same time; requests beyond it are rejected
+    /// with 503 Service Unavailable. Unlimited if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_inflight_requests: Option<NonZeroUsize>,
+    /// Serves the broker over TLS when present; otherwise, the broker serves plaintext HTTP.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Path to the PEM encoded certificate chain.
+    pub cert_path: String,
+    /// Path to the PEM encoded private key.
+    pub key_path: String,
+    /// Path to the PEM encoded CA certificates for verifying client certificates. When present,
+    /// clients must authenticate with a certificate signed by one of these CAs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ca_path: Option<String>,
 }
@@ -16,10 +16,12 @@ mod broker;
 mod meta;
 mod runtime;
 mod server;
+mod storage;
 mod telemetry;
 
 pub use broker::*;
 pub use meta::*;
 pub use runtime::*;
 pub use server::*;
+pub use storage::*;
 pub use telemetry::*;
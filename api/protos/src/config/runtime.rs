@@ -27,4 +27,6 @@ pub struct RuntimeOptions {
     pub exec_runtime_threads: Option<NonZeroUsize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub io_runtime_threads: Option<NonZeroUsize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_runtime_threads: Option<NonZeroUsize>,
 }
@@ -17,9 +17,22 @@ use serde::Serialize;
 
 use crate::config::BrokerConfig;
 use crate::config::MetaServiceConfig;
+use crate::config::StorageConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub meta: MetaServiceConfig,
     pub broker: BrokerConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+}
+
+impl ServerConfig {
+    /// Returns a copy of this config with secrets masked, so that it can be logged.
+    pub fn redacted(&self) -> Self {
+        Self {
+            meta: self.meta.redacted(),
+            ..self.clone()
+        }
+    }
 }
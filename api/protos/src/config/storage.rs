@@ -0,0 +1,77 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Options shared by the storages of all topics served by this node.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StorageConfig {
+    /// The maximum total bytes of recently read splits kept in memory; no cache if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_cache_bytes: Option<NonZeroUsize>,
+    /// The maximum times to retry a storage operation that failed with a temporary error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_max_times: Option<usize>,
+    /// The delay in milliseconds before the first retry; it grows exponentially with jitter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_min_delay_ms: Option<u64>,
+    /// The maximum number of storage operations in flight at the same time; further operations
+    /// wait for a slot. Defaults to 16.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_ops: Option<NonZeroUsize>,
+    /// How often in milliseconds to look for splits past the retention of their topics; defaults
+    /// to 60 seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_check_interval_ms: Option<u64>,
+    /// Faults injected into the storage operations for testing; only honored by the builds of
+    /// morax-storage with the `fault-injection` feature.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub faults: Vec<StorageFault>,
+}
+
+/// A storage operation that faults can be injected into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageOperation {
+    Read,
+    Write,
+    Delete,
+}
+
+/// What an injected storage fault does to the operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageFaultKind {
+    /// Fails the operation with an unexpected error before it reaches the storage.
+    Unexpected,
+    /// Fails the operation with a temporary error before it reaches the storage.
+    Temporary,
+    /// Flips the bytes read from the storage, as if the object were corrupted; only applies to
+    /// reads.
+    Corrupt,
+}
+
+/// Injects a fault of `kind` into the storage operations of `operation`, at `ratio` between 0.0
+/// and 1.0 of the attempts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StorageFault {
+    pub operation: StorageOperation,
+    pub kind: StorageFaultKind,
+    pub ratio: f64,
+}
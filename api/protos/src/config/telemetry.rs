@@ -19,21 +19,97 @@ use serde::Serialize;
 pub struct TelemetryConfig {
     #[serde(default = "LogConfig::disabled")]
     pub log: LogConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traces: Option<TracesConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TracesConfig {
+    /// The OTLP/HTTP endpoint to export spans to, e.g., `http://127.0.0.1:4318/v1/traces`.
+    pub otlp_endpoint: String,
+    /// The fraction of requests, between 0.0 and 1.0, whose traces are exported; defaults to 1.0.
+    ///
+    /// Requests are sampled by their trace id, i.e., the `x-request-id` of the response, when they
+    /// start; no spans are recorded for a request that is not sampled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_ratio: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stderr: Option<StderrAppenderConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileAppenderConfig>,
 }
 
 impl LogConfig {
     pub fn disabled() -> Self {
-        Self { stderr: None }
+        Self {
+            stderr: None,
+            file: None,
+        }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StderrAppenderConfig {
     pub filter: String,
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileAppenderConfig {
+    pub filter: String,
+    /// The directory to write log files into.
+    pub dir: String,
+    /// The prefix of log file names.
+    pub name: String,
+    #[serde(default)]
+    pub format: LogFormat,
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// The maximum number of log files to retain; unlimited if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, with the trace id of the current span if any.
+    Json,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_defaults_to_text() {
+        let config: StderrAppenderConfig = toml::from_str(r#"filter = "INFO""#).unwrap();
+        assert_eq!(config.format, LogFormat::Text);
+
+        let config: StderrAppenderConfig = toml::from_str(
+            r#"filter = "INFO"
+format = "json""#,
+        )
+        .unwrap();
+        assert_eq!(config.format, LogFormat::Json);
+    }
 }
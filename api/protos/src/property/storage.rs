@@ -12,12 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroU64;
+use std::num::NonZeroUsize;
+
 use serde::Deserialize;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopicProps {
     pub storage: StorageProps,
+    /// Whether to read back and compare each split after writing it, before acknowledging the
+    /// append. This guards against storages with weak read-after-write consistency at the cost of
+    /// an extra read per append.
+    #[serde(default)]
+    pub verify_writes: bool,
+    /// The maximum size in bytes of a split; appends that would write a larger split are rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_split_bytes: Option<NonZeroUsize>,
+    /// How long in milliseconds to keep a split after it is committed; splits are kept forever if
+    /// unset. Expired splits are deleted in the background, so reads may still see them for a
+    /// while.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_ms: Option<NonZeroU64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,4 +41,10 @@ pub struct TopicProps {
 pub enum StorageProps {
     #[serde(rename = "s3")]
     S3(opendal::services::S3Config),
+    #[serde(rename = "fs")]
+    Fs(opendal::services::FsConfig),
+    #[serde(rename = "gcs")]
+    Gcs(opendal::services::GcsConfig),
+    #[serde(rename = "azblob")]
+    Azblob(opendal::services::AzblobConfig),
 }
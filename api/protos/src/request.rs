@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroUsize;
 use std::ops::Range;
 
 use serde::Deserialize;
@@ -37,6 +38,11 @@ pub struct Entry {
 pub struct CreateLogRequest {
     pub name: String,
     pub properties: TopicProps,
+    /// Whether to succeed with the existing log, rather than failing with
+    /// [`ErrorCode::AlreadyExists`], if a log with the same name and properties exists. A log with
+    /// the same name but different properties still fails.
+    #[serde(default)]
+    pub if_not_exists: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,10 +50,24 @@ pub struct CreateLogResponse {
     pub name: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteLogRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteLogResponse {
+    pub name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppendLogRequest {
     pub name: String,
     pub entries: Vec<Entry>,
+    /// Appends the entries only if the end offset of the log equals this offset; otherwise, the
+    /// append fails with [`ErrorCode::OffsetConflict`] and the current end offset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_offset: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,15 +76,76 @@ pub struct AppendLogResponse {
     pub offsets: Range<i64>,
 }
 
+/// Reads entries from `offset`, which must be within the start and the end offsets of the log.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadLogRequest {
     pub name: String,
     pub offset: i64,
+    /// The maximum total bytes of the entries to return, so that a large log can be read in
+    /// pages; at least one entry is returned if any. Reads to the end of the log if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<NonZeroUsize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadLogResponse {
     pub entries: Vec<Entry>,
+    /// The offset to read the next page from.
+    pub next_offset: i64,
+    /// The first readable offset of the log; the entries below it are trimmed or expired.
+    pub start_offset: i64,
+    /// The offset the next entry will be appended at; a reader at `next_offset` is behind the
+    /// tail of the log by `end_offset - next_offset` entries.
+    pub end_offset: i64,
+}
+
+/// Trims the log so that the entries below `offset` are no longer readable, e.g., to purge data or
+/// reclaim space without deleting the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrimLogRequest {
+    pub name: String,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrimLogResponse {
+    pub name: String,
+    /// The first readable offset of the log after trimming; trimming below it is a no-op.
+    pub start_offset: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealLogResponse {
+    pub name: String,
+    /// Whether the log rejects appends now.
+    pub sealed: bool,
+}
+
+/// Reads entries from `offset` and keeps streaming newly appended entries, as newline-delimited
+/// JSON [`Entry`] objects, until the client disconnects or the timeout elapses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowLogRequest {
+    pub name: String,
+    pub offset: i64,
+    /// How long to follow the log; defaults to 60 seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// A summary of a split object decoded as is, for debugging mismatches between the storage and
+/// the metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DescribeSplitResponse {
+    pub split_id: String,
+    pub log_name: String,
+    /// The format the split is written in, e.g., "v1" or "flexbuffers".
+    pub format: String,
+    /// The size of the split object in bytes.
+    pub size_bytes: usize,
+    /// The number of entries decoded from the split object.
+    pub entry_count: usize,
+    /// The half-open offset range committed for the split in the metadata.
+    pub offsets: Range<i64>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -72,12 +153,40 @@ pub enum ErrorCode {
     /// The broker does not know what happened here, and no actions other than just returning it
     /// back.
     Unexpected,
+    /// The requested resource does not exist.
+    NotFound,
+    /// The resource to create already exists.
+    AlreadyExists,
+    /// The request is malformed or violates a limit of the broker; retrying it as is won't help.
+    InvalidArgument,
+    /// The broker cannot serve the request for now, e.g., it is shutting down; the request can be
+    /// retried as is, possibly against another broker.
+    Unavailable,
+    /// The requested offset is out of the readable range of the log, e.g., it has been trimmed
+    /// or expired.
+    OutOfRange,
+    /// The meta and the storage are out of sync, e.g., a committed split is missing in the storage
+    /// because it was deleted out of band; retrying won't help.
+    DataLoss,
+    /// The end offset of the log does not match the expected offset of a conditional append; the
+    /// current end offset is returned in [`ErrorResponse::end_offset`].
+    OffsetConflict,
+    /// The log is sealed to appends; it can still be read, and be unsealed.
+    Sealed,
 }
 
 impl std::fmt::Display for ErrorCode {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ErrorCode::Unexpected => write!(f, "unexpected"),
+            ErrorCode::NotFound => write!(f, "not found"),
+            ErrorCode::AlreadyExists => write!(f, "already exists"),
+            ErrorCode::InvalidArgument => write!(f, "invalid argument"),
+            ErrorCode::Unavailable => write!(f, "unavailable"),
+            ErrorCode::OutOfRange => write!(f, "out of range"),
+            ErrorCode::DataLoss => write!(f, "data loss"),
+            ErrorCode::OffsetConflict => write!(f, "offset conflict"),
+            ErrorCode::Sealed => write!(f, "sealed"),
         }
     }
 }
@@ -86,8 +195,15 @@ impl std::fmt::Display for ErrorCode {
 pub struct ErrorResponse {
     pub code: ErrorCode,
     pub message: String,
+    /// The current end offset of the log, for [`ErrorCode::OffsetConflict`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<i64>,
 }
 
+/// The current end offset of a log, attached to the error of a conflicting conditional append.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogEndOffset(pub i64);
+
 impl std::fmt::Display for ErrorResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{} ({}): {}", self.code, self.code as u32, self.message)
@@ -69,7 +69,7 @@ impl CommandStart {
         };
 
         morax_runtime::init(&config.runtime);
-        morax_telemetry::init(&config.telemetry);
+        let _telemetry_guards = morax_telemetry::init(&config.telemetry);
 
         error_stack::Report::set_color_mode(error_stack::fmt::ColorMode::None);
         error_stack::Report::set_charset(error_stack::fmt::Charset::Ascii);
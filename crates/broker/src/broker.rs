@@ -12,45 +12,88 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use error_stack::Report;
 use error_stack::Result;
 use error_stack::ResultExt;
+use futures::Stream;
+use futures::StreamExt;
+use morax_meta::CommitIndeterminate;
 use morax_meta::CommitRecordBatchesRequest;
 use morax_meta::CreateTopicRequest;
 use morax_meta::FetchRecordBatchesRequest;
-use morax_meta::PostgresMetaService;
+use morax_meta::MetaService;
+use morax_meta::Topic;
+use morax_meta::TopicSplit;
 use morax_protos::request::AppendLogRequest;
 use morax_protos::request::AppendLogResponse;
 use morax_protos::request::CreateLogRequest;
 use morax_protos::request::CreateLogResponse;
+use morax_protos::request::DeleteLogRequest;
+use morax_protos::request::DeleteLogResponse;
+use morax_protos::request::DescribeSplitResponse;
 use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::FollowLogRequest;
 use morax_protos::request::ReadLogRequest;
 use morax_protos::request::ReadLogResponse;
+use morax_protos::request::SealLogResponse;
+use morax_protos::request::TrimLogRequest;
+use morax_protos::request::TrimLogResponse;
+use morax_storage::StorageContext;
+use morax_storage::StorageError;
 use morax_storage::TopicStorage;
-use serde::Deserialize;
-use serde::Serialize;
 
+use crate::notify::AppendNotifier;
+use crate::split::decode_split;
+use crate::split::describe_split;
+use crate::split::encode_split;
 use crate::BrokerError;
+use crate::DrainTracker;
 
-// TODO(tisonkun): figure out whether flexbuffers is the proper format
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct EntryData {
-    data: Vec<u8>,
+const DEFAULT_FOLLOW_TIMEOUT: Duration = Duration::from_secs(60);
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct FollowState {
+    broker: Broker,
+    name: String,
+    offset: i64,
+    deadline: tokio::time::Instant,
+    pending: VecDeque<Entry>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Broker {
-    meta: Arc<PostgresMetaService>,
+    meta: Arc<dyn MetaService>,
+    storage: StorageContext,
+    drain: Arc<DrainTracker>,
+    appends: Arc<AppendNotifier>,
+    max_append_entries: usize,
 }
 
 impl Broker {
-    pub fn new(meta: Arc<PostgresMetaService>) -> Self {
-        Broker { meta }
+    pub fn new(
+        meta: Arc<dyn MetaService>,
+        storage: StorageContext,
+        drain: Arc<DrainTracker>,
+        max_append_entries: usize,
+    ) -> Self {
+        Broker {
+            meta,
+            storage,
+            drain,
+            appends: Arc::new(AppendNotifier::default()),
+            max_append_entries,
+        }
     }
 
+    #[fastrace::trace]
     pub async fn create(
         &self,
         request: CreateLogRequest,
@@ -63,6 +106,7 @@ impl Broker {
             .create_topic(CreateTopicRequest {
                 name: name.clone(),
                 properties: request.properties,
+                if_not_exists: request.if_not_exists,
             })
             .await
             .change_context_lazy(make_error)?;
@@ -70,6 +114,107 @@ impl Broker {
         Ok(CreateLogResponse { name: topic.name })
     }
 
+    #[fastrace::trace]
+    pub async fn delete(
+        &self,
+        request: DeleteLogRequest,
+    ) -> Result<DeleteLogResponse, BrokerError> {
+        let name = request.name;
+        let make_error = || BrokerError(format!("failed to delete log {name}"));
+
+        let Some((topic, splits)) = self
+            .meta
+            .delete_topic(name.clone())
+            .await
+            .change_context_lazy(make_error)?
+        else {
+            return Err(Report::new(make_error()).attach(ErrorCode::NotFound));
+        };
+        self.appends.remove(&topic.name);
+
+        // Split objects are removed in the background so that the request returns quickly. If the
+        // cleanup fails, the remaining objects are left orphaned in the storage and must be removed
+        // manually; they are never read again since their metadata is already gone.
+        let topic_name = topic.name.clone();
+        let topic_storage = TopicStorage::new(topic.properties.0.storage, self.storage.clone());
+        let split_ids = splits
+            .into_iter()
+            .map(|split| split.split_id)
+            .collect::<Vec<_>>();
+        morax_runtime::io_runtime().spawn(async move {
+            if let Err(err) = topic_storage.delete_splits(&topic_name, &split_ids).await {
+                log::warn!(err:?; "failed to delete splits of log {topic_name}");
+            }
+        });
+
+        Ok(DeleteLogResponse { name: topic.name })
+    }
+
+    #[fastrace::trace]
+    pub async fn trim(&self, request: TrimLogRequest) -> Result<TrimLogResponse, BrokerError> {
+        let name = request.name;
+        let make_error = || BrokerError(format!("failed to trim log {name}"));
+
+        let topic = self
+            .meta
+            .get_topics_by_name(name.clone())
+            .await
+            .change_context_lazy(make_error)?;
+        let (start_offset, splits) = self
+            .meta
+            .trim_topic(name.clone(), request.offset)
+            .await
+            .change_context_lazy(make_error)?;
+
+        // Same as deleting a log, the trimmed split objects are removed in the background and
+        // left orphaned if the cleanup fails. A split that straddles the start offset is kept.
+        if !splits.is_empty() {
+            let topic_name = topic.name.clone();
+            let topic_storage = TopicStorage::new(topic.properties.0.storage, self.storage.clone());
+            let split_ids = splits
+                .into_iter()
+                .map(|split| split.split_id)
+                .collect::<Vec<_>>();
+            morax_runtime::io_runtime().spawn(async move {
+                if let Err(err) = topic_storage.delete_splits(&topic_name, &split_ids).await {
+                    log::warn!(err:?; "failed to delete trimmed splits of log {topic_name}");
+                }
+            });
+        }
+
+        Ok(TrimLogResponse {
+            name: topic.name,
+            start_offset,
+        })
+    }
+
+    /// Seals the log to appends, or unseals it if `sealed` is false; the log can still be read.
+    #[fastrace::trace]
+    pub async fn seal(&self, name: String, sealed: bool) -> Result<SealLogResponse, BrokerError> {
+        let make_error = || BrokerError(format!("failed to seal log {name}"));
+
+        self.meta
+            .seal_topic(name.clone(), sealed)
+            .await
+            .change_context_lazy(make_error)?;
+
+        Ok(SealLogResponse { name, sealed })
+    }
+
+    /// Checks that the dependencies of the broker are reachable within `timeout`.
+    pub async fn ready(&self, timeout: Duration) -> Result<(), BrokerError> {
+        let make_error = || BrokerError("meta service is not ready".to_string());
+
+        match tokio::time::timeout(timeout, self.meta.ping()).await {
+            Ok(result) => result.change_context_lazy(make_error),
+            Err(_) => {
+                Err(Report::new(make_error())
+                    .attach_printable(format!("timed out after {timeout:?}")))
+            }
+        }
+    }
+
+    #[fastrace::trace]
     pub async fn read_at(&self, request: ReadLogRequest) -> Result<ReadLogResponse, BrokerError> {
         let name = request.name;
         let make_error = || BrokerError(format!("failed to read log from {name}"));
@@ -80,7 +225,7 @@ impl Broker {
             .await
             .change_context_lazy(make_error)?;
 
-        let topic_storage = TopicStorage::new(topic.properties.0.storage);
+        let topic_storage = TopicStorage::new(topic.properties.0.storage, self.storage.clone());
 
         let splits = self
             .meta
@@ -92,33 +237,191 @@ impl Broker {
             .await
             .change_context_lazy(make_error)?;
 
+        // splits are read concurrently, bounded by the storage, and decoded in offsets order
+        let topic_storage = &topic_storage;
+        let mut reads = futures::stream::iter(splits)
+            .map(|split| async move {
+                let data = topic_storage
+                    .read_at(&split.topic_name, &split.split_id)
+                    .await;
+                (split, data)
+            })
+            .buffered(self.storage.max_concurrent_ops());
+
+        let max_bytes = request.max_bytes.map_or(usize::MAX, NonZeroUsize::get);
+        let mut read_bytes = 0;
         let mut entries = vec![];
-        for split in splits {
+        'read: while let Some((split, data)) = reads.next().await {
             debug_assert_eq!(&split.topic_name, &topic.name);
-            let data = topic_storage
-                .read_at(&split.topic_name, &split.split_id)
-                .await
-                .change_context_lazy(make_error)?;
-            let deserializer =
-                flexbuffers::Reader::get_root(data.as_slice()).change_context_lazy(|| {
-                    BrokerError("failed to deserialize entry data".to_string())
-                })?;
-            let entry_data =
-                Vec::<EntryData>::deserialize(deserializer).change_context_lazy(|| {
-                    BrokerError("failed to deserialize entry data".to_string())
-                })?;
-            for (i, entry_data) in entry_data.into_iter().enumerate() {
-                if split.start_offset + i as i64 >= request.offset {
-                    entries.push(Entry {
-                        index: Some(split.start_offset + i as i64),
-                        data: BASE64_STANDARD.encode(&entry_data.data),
-                    });
+            let data = match data {
+                Ok(data) => data,
+                Err(err) => {
+                    let err = self.classify_read_failure(&topic, &split, err).await;
+                    return Err(err.change_context(make_error()));
                 }
+            };
+            let skip = (request.offset - split.start_offset).max(0);
+            // entries are decoded as they are taken, so that those past max_bytes are not decoded
+            let entry_data = decode_split(&data, skip as usize)?;
+            for (i, entry_data) in entry_data.enumerate() {
+                let entry_data = entry_data?;
+                // the first entry is always returned so that paging readers make progress
+                if !entries.is_empty() && read_bytes + entry_data.len() > max_bytes {
+                    break 'read;
+                }
+                read_bytes += entry_data.len();
+                entries.push(Entry {
+                    index: Some(split.start_offset + skip + i as i64),
+                    data: BASE64_STANDARD.encode(&entry_data),
+                });
             }
         }
-        Ok(ReadLogResponse { entries })
+
+        metrics::counter!("morax_broker_read_bytes_total").increment(read_bytes as u64);
+
+        let next_offset = entries
+            .last()
+            .and_then(|entry| entry.index)
+            .map_or(request.offset, |index| index + 1);
+
+        // resolved after the splits, so that the end offset covers all the entries read
+        let (start_offset, end_offset) = self
+            .meta
+            .get_topic_offsets(topic.id)
+            .await
+            .change_context_lazy(make_error)?;
+
+        Ok(ReadLogResponse {
+            entries,
+            next_offset,
+            start_offset,
+            end_offset,
+        })
+    }
+
+    /// Attaches the error code of a split missing in the storage: out of range if the split has
+    /// been trimmed or expired since it was resolved, or data loss otherwise.
+    async fn classify_read_failure(
+        &self,
+        topic: &Topic,
+        split: &TopicSplit,
+        err: Report<StorageError>,
+    ) -> Report<StorageError> {
+        if !matches!(err.current_context(), StorageError::SplitMissing(_)) {
+            return err;
+        }
+        match self.meta.get_topic_offsets(topic.id).await {
+            Ok((start_offset, _)) if split.end_offset <= start_offset => err
+                .attach_printable(format!(
+                    "split {} is below the start offset {start_offset}",
+                    split.split_id
+                ))
+                .attach(ErrorCode::OutOfRange),
+            _ => err.attach(ErrorCode::DataLoss),
+        }
     }
 
+    /// Reads the split of `split_id` as is and summarizes it, bypassing the offsets resolution.
+    #[fastrace::trace]
+    pub async fn describe_split(
+        &self,
+        split_id: String,
+    ) -> Result<DescribeSplitResponse, BrokerError> {
+        let make_error = || BrokerError(format!("failed to describe split {split_id}"));
+
+        let split = self
+            .meta
+            .get_split(split_id.clone())
+            .await
+            .change_context_lazy(make_error)?;
+        let topic = self
+            .meta
+            .get_topics_by_name(split.topic_name.clone())
+            .await
+            .change_context_lazy(make_error)?;
+
+        let topic_storage = TopicStorage::new(topic.properties.0.storage, self.storage.clone());
+        let data = topic_storage
+            .read_at(&split.topic_name, &split.split_id)
+            .await
+            .change_context_lazy(make_error)?;
+        let (format, entry_count) = describe_split(&data).change_context_lazy(make_error)?;
+
+        Ok(DescribeSplitResponse {
+            split_id: split.split_id,
+            log_name: split.topic_name,
+            format: format.to_string(),
+            size_bytes: data.len(),
+            entry_count,
+            offsets: split.start_offset..split.end_offset,
+        })
+    }
+
+    /// Returns a stream of the entries from `request.offset`, which keeps yielding newly appended
+    /// entries until the timeout elapses or the broker shuts down.
+    pub async fn follow(
+        &self,
+        request: FollowLogRequest,
+    ) -> Result<impl Stream<Item = Result<Entry, BrokerError>> + Send + 'static, BrokerError> {
+        let name = request.name;
+        let make_error = || BrokerError(format!("failed to follow log {name}"));
+
+        // fail fast before the response starts streaming
+        self.meta
+            .get_topics_by_name(name.clone())
+            .await
+            .change_context_lazy(make_error)?;
+
+        let timeout = request
+            .timeout_ms
+            .map_or(DEFAULT_FOLLOW_TIMEOUT, Duration::from_millis);
+        let state = FollowState {
+            broker: self.clone(),
+            name,
+            offset: request.offset,
+            deadline: tokio::time::Instant::now() + timeout,
+            pending: VecDeque::new(),
+        };
+        Ok(futures::stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+            loop {
+                if let Some(entry) = state.pending.pop_front() {
+                    return Some((Ok(entry), Some(state)));
+                }
+
+                let notify = state.broker.appends.subscribe(&state.name);
+                let notified = notify.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                let request = ReadLogRequest {
+                    name: state.name.clone(),
+                    offset: state.offset,
+                    max_bytes: None,
+                };
+                match state.broker.read_at(request).await {
+                    Ok(response) => {
+                        state.offset = response.next_offset;
+                        state.pending.extend(response.entries);
+                        if !state.pending.is_empty() {
+                            continue;
+                        }
+                    }
+                    Err(err) => return Some((Err(err), None)),
+                }
+
+                // appends served by other brokers are not notified, so poll periodically as well
+                tokio::select! {
+                    _ = notified => {}
+                    _ = tokio::time::sleep(FOLLOW_POLL_INTERVAL) => {}
+                    _ = tokio::time::sleep_until(state.deadline) => return None,
+                    _ = state.broker.drain.wait_draining() => return None,
+                }
+            }
+        }))
+    }
+
+    #[fastrace::trace]
     pub async fn append(
         &self,
         request: AppendLogRequest,
@@ -126,51 +429,304 @@ impl Broker {
         let name = request.name;
         let make_error = || BrokerError(format!("failed to append log to {name}"));
 
+        // new appends are not accepted once shutting down, so that the in-flight ones can drain
+        if self.drain.is_draining() {
+            return Err(Report::new(make_error())
+                .attach_printable("broker is shutting down")
+                .attach(ErrorCode::Unavailable));
+        }
+
+        let entry_cnt = request.entries.len();
+        if entry_cnt > self.max_append_entries {
+            let max_append_entries = self.max_append_entries;
+            return Err(Report::new(make_error())
+                .attach_printable(format!(
+                    "too many entries to append: {entry_cnt} > {max_append_entries}; \
+                    split the entries into smaller batches"
+                ))
+                .attach(ErrorCode::InvalidArgument));
+        }
+
         let topic = self
             .meta
             .get_topics_by_name(name.clone())
             .await
             .change_context_lazy(make_error)?;
 
-        let topic_storage = TopicStorage::new(topic.properties.0.storage);
+        let verify_writes = topic.properties.0.verify_writes;
+        let max_split_bytes = topic.properties.0.max_split_bytes;
+        let topic_storage = TopicStorage::new(topic.properties.0.storage, self.storage.clone());
 
-        let entry_cnt = request.entries.len();
         let entry_data = {
             let mut entry_data = vec![];
             for entry in request.entries.into_iter() {
-                entry_data.push(EntryData {
-                    data: BASE64_STANDARD
+                entry_data.push(
+                    BASE64_STANDARD
                         .decode(entry.data.as_bytes())
                         .change_context_lazy(|| {
                             BrokerError(format!("failed to decode base64: {:?}", entry.data))
                         })?,
-                });
+                );
             }
-            let mut serializer = flexbuffers::FlexbufferSerializer::new();
-            entry_data
-                .serialize(&mut serializer)
-                .change_context_lazy(|| {
-                    BrokerError("failed to serialize entry data".to_string())
-                })?;
-            serializer.take_buffer()
+            encode_split(&entry_data)
         };
+        let entry_bytes = entry_data.len();
+        if let Some(max_split_bytes) = max_split_bytes {
+            if entry_bytes > max_split_bytes.get() {
+                return Err(Report::new(make_error())
+                    .attach_printable(format!(
+                        "split too large: {entry_bytes} bytes > {max_split_bytes} bytes; \
+                        split the entries into smaller batches"
+                    ))
+                    .attach(ErrorCode::InvalidArgument));
+            }
+        }
+        let expected_data = verify_writes.then(|| entry_data.clone());
         let split_id = topic_storage
             .write_to(&topic.name, entry_data)
             .await
             .change_context_lazy(make_error)?;
+        if let Some(expected_data) = expected_data {
+            let verified = topic_storage
+                .verify_split(&topic.name, &split_id, &expected_data)
+                .await;
+            if let Err(err) = verified {
+                // a split that fails to be read back may still be written as expected, and is left
+                // orphaned in the storage the same as one whose commit may have landed
+                if is_verify_rejected(&err) {
+                    spawn_delete_orphan_split(topic_storage, topic.name, split_id);
+                }
+                return Err(err.change_context(make_error()));
+            }
+        }
+        self.drain.record_split_flushed();
 
-        let (start_offset, end_offset) = self
+        let commit = self
             .meta
             .commit_record_batches(CommitRecordBatchesRequest {
                 topic_name: name.clone(),
                 record_len: entry_cnt as i32,
-                split_id,
+                split_id: split_id.clone(),
+                expected_offset: request.expected_offset,
             })
-            .await
-            .change_context_lazy(make_error)?;
+            .await;
+        let (start_offset, end_offset) = match commit {
+            Ok(offsets) => offsets,
+            Err(err) => {
+                // a commit that fails after it started to apply may have landed, and deleting its
+                // split would lose committed records; such a split is left orphaned instead
+                if !err.contains::<CommitIndeterminate>() {
+                    spawn_delete_orphan_split(topic_storage, topic.name, split_id);
+                }
+                return Err(err.change_context(make_error()));
+            }
+        };
 
+        self.appends.notify(&name);
+        metrics::counter!("morax_broker_append_bytes_total").increment(entry_bytes as u64);
         Ok(AppendLogResponse {
             offsets: start_offset..end_offset,
         })
     }
 }
+
+/// Whether a split is definitely not written as expected, as opposed to failing to be read back.
+fn is_verify_rejected(err: &Report<StorageError>) -> bool {
+    matches!(
+        err.current_context(),
+        StorageError::SplitMismatch(_) | StorageError::SplitMissing(_)
+    )
+}
+
+/// Deletes a split that is never committed, and thus never visible to readers, in the background.
+fn spawn_delete_orphan_split(topic_storage: TopicStorage, topic_name: String, split_id: String) {
+    morax_runtime::io_runtime().spawn(async move {
+        if let Err(err) = topic_storage.delete_splits(&topic_name, &[split_id]).await {
+            log::warn!(err:?; "failed to delete orphan split of log {topic_name}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use morax_meta::MetaError;
+    use morax_protos::config::StorageConfig;
+    use morax_protos::config::StorageFault;
+    use morax_protos::config::StorageFaultKind;
+    use morax_protos::config::StorageOperation;
+    use morax_protos::property::StorageProps;
+    use morax_protos::property::TopicProps;
+    use opendal::services::FsConfig;
+    use sqlx::types::Json;
+
+    use super::*;
+
+    /// A meta service for the checks done before touching the metadata, or serving `topic` and
+    /// failing every commit to it with `commit_error`.
+    #[derive(Debug, Default)]
+    struct StubMeta {
+        topic: Option<Topic>,
+        commit_error: Option<fn() -> Report<MetaError>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MetaService for StubMeta {
+        async fn get_topics_by_name(&self, _: String) -> Result<Topic, MetaError> {
+            Ok(self.topic.clone().expect("no topic to serve"))
+        }
+
+        async fn commit_record_batches(
+            &self,
+            _: CommitRecordBatchesRequest,
+        ) -> Result<(i64, i64), MetaError> {
+            let make_error = self.commit_error.expect("no commit error to inject");
+            Err(make_error())
+        }
+    }
+
+    #[test]
+    fn test_reject_appends_when_draining() {
+        let drain = Arc::new(DrainTracker::default());
+        let broker = Broker::new(
+            Arc::new(StubMeta::default()),
+            StorageContext::default(),
+            drain.clone(),
+            10,
+        );
+        drain.start_drain();
+
+        let request = AppendLogRequest {
+            name: "log".to_string(),
+            entries: vec![Entry {
+                index: None,
+                data: "MA==".to_string(),
+            }],
+            expected_offset: None,
+        };
+        let err = morax_runtime::test_runtime()
+            .block_on(broker.append(request))
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ErrorCode>().copied(),
+            Some(ErrorCode::Unavailable)
+        );
+    }
+
+    #[test]
+    fn test_verify_rejected() {
+        let mismatch = Report::new(StorageError::SplitMismatch("split".to_string()));
+        assert!(is_verify_rejected(&mismatch));
+        let missing = Report::new(StorageError::SplitMissing("split".to_string()));
+        assert!(is_verify_rejected(&missing));
+        let unreadable = Report::new(StorageError::OpenDAL(opendal::Error::new(
+            opendal::ErrorKind::Unexpected,
+            "connection reset",
+        )));
+        assert!(!is_verify_rejected(&unreadable));
+    }
+
+    /// A log whose splits are written to `dir` as `dir/log/<split_id>`.
+    fn make_fs_topic(dir: &Path) -> Topic {
+        let mut config = FsConfig::default();
+        config.root = Some(dir.to_string_lossy().to_string());
+        Topic {
+            id: uuid::Uuid::new_v4(),
+            name: "log".to_string(),
+            properties: Json(TopicProps {
+                storage: StorageProps::Fs(config),
+                verify_writes: false,
+                max_split_bytes: None,
+                retention_ms: None,
+            }),
+        }
+    }
+
+    fn count_splits(dir: &Path) -> usize {
+        std::fs::read_dir(dir.join("log")).map_or(0, Iterator::count)
+    }
+
+    fn append_one_entry(meta: StubMeta, storage: StorageContext) -> Report<BrokerError> {
+        let broker = Broker::new(
+            Arc::new(meta),
+            storage,
+            Arc::new(DrainTracker::default()),
+            10,
+        );
+        let request = AppendLogRequest {
+            name: "log".to_string(),
+            entries: vec![Entry {
+                index: None,
+                data: "MA==".to_string(),
+            }],
+            expected_offset: None,
+        };
+        morax_runtime::test_runtime()
+            .block_on(broker.append(request))
+            .unwrap_err()
+    }
+
+    #[test]
+    fn test_fail_appends_on_storage_faults() {
+        let dir = tempfile::tempdir().unwrap();
+        let meta = StubMeta {
+            topic: Some(make_fs_topic(dir.path())),
+            commit_error: None,
+        };
+        let storage = StorageContext::new(&StorageConfig {
+            faults: vec![StorageFault {
+                operation: StorageOperation::Write,
+                kind: StorageFaultKind::Unexpected,
+                ratio: 1.0,
+            }],
+            ..StorageConfig::default()
+        });
+
+        // the split is never written, so there is nothing to commit or to clean up
+        let err = append_one_entry(meta, storage);
+        assert!(matches!(
+            err.downcast_ref::<StorageError>(),
+            Some(StorageError::OpenDAL(_))
+        ));
+        assert_eq!(count_splits(dir.path()), 0);
+    }
+
+    #[test]
+    fn test_keep_split_of_indeterminate_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let topic = make_fs_topic(dir.path());
+        let append = |commit_error: fn() -> Report<MetaError>| {
+            let meta = StubMeta {
+                topic: Some(topic.clone()),
+                commit_error: Some(commit_error),
+            };
+            append_one_entry(meta, StorageContext::default())
+        };
+
+        // the commit row may be written before the failure, so the split must be kept
+        let err = append(|| {
+            Report::new(MetaError("connection reset".to_string()))
+                .attach(ErrorCode::Unavailable)
+                .attach(CommitIndeterminate)
+        });
+        assert!(err.contains::<CommitIndeterminate>());
+        assert_eq!(count_splits(dir.path()), 1);
+
+        // a rejected split is never visible to readers, so it is deleted in the background
+        let err = append(|| {
+            Report::new(MetaError("topic log is sealed".to_string())).attach(ErrorCode::Sealed)
+        });
+        assert_eq!(
+            err.downcast_ref::<ErrorCode>().copied(),
+            Some(ErrorCode::Sealed)
+        );
+        for _ in 0..100 {
+            if count_splits(dir.path()) == 1 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(count_splits(dir.path()), 1);
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use tokio::sync::Notify;
+
+/// Tracks the requests served by a broker so that a graceful shutdown can report what happened to
+/// the work in flight when the shutdown started.
+#[derive(Debug, Default)]
+pub struct DrainTracker {
+    draining: AtomicBool,
+    inflight: AtomicU64,
+    drained: AtomicU64,
+    completed: AtomicU64,
+    splits_flushed: AtomicU64,
+    draining_notify: Notify,
+}
+
+/// A summary of the work drained by a broker during its graceful shutdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Number of requests in flight when the shutdown started.
+    pub requests_drained: u64,
+    /// Number of requests completed after the shutdown started.
+    pub requests_completed: u64,
+    /// Number of splits written to the storage after the shutdown started.
+    pub splits_flushed: u64,
+    /// Number of requests still in flight when the graceful shutdown period elapsed.
+    pub forced_cancellations: u64,
+}
+
+impl DrainTracker {
+    /// Marks the start of the graceful shutdown; requests finished from now on count as drained.
+    pub fn start_drain(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+        self.draining_notify.notify_waiters();
+        let inflight = self.inflight.load(Ordering::SeqCst);
+        self.drained.store(inflight, Ordering::Relaxed);
+    }
+
+    /// Whether the graceful shutdown has started.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    pub fn report(&self) -> DrainReport {
+        DrainReport {
+            requests_drained: self.drained.load(Ordering::Relaxed),
+            requests_completed: self.completed.load(Ordering::Relaxed),
+            splits_flushed: self.splits_flushed.load(Ordering::Relaxed),
+            forced_cancellations: self.inflight.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Tracks a new request in flight, unless there are already `limit` requests in flight.
+    pub(crate) fn try_enter(
+        tracker: Arc<DrainTracker>,
+        limit: Option<NonZeroUsize>,
+    ) -> Option<InflightGuard> {
+        let inflight = tracker.inflight.fetch_add(1, Ordering::SeqCst);
+        if limit.is_some_and(|limit| inflight >= limit.get() as u64) {
+            tracker.inflight.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        metrics::gauge!("morax_broker_inflight_requests").increment(1);
+        Some(InflightGuard { tracker })
+    }
+
+    /// Resolves once the graceful shutdown starts.
+    pub(crate) async fn wait_draining(&self) {
+        let notified = self.draining_notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        if self.draining.load(Ordering::SeqCst) {
+            return;
+        }
+        notified.await;
+    }
+
+    pub(crate) fn record_split_flushed(&self) {
+        if self.draining.load(Ordering::SeqCst) {
+            self.splits_flushed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Untracks the request on drop, including when the request is cancelled because the client
+/// disconnects.
+pub(crate) struct InflightGuard {
+    tracker: Arc<DrainTracker>,
+}
+
+/// Holds the guard of a request until the endpoint takes it, so that a streamed response keeps
+/// the request in flight until the stream ends rather than when the endpoint returns.
+#[derive(Clone)]
+pub(crate) struct InflightSlot(Arc<Mutex<Option<InflightGuard>>>);
+
+impl InflightSlot {
+    pub(crate) fn new(guard: Option<InflightGuard>) -> Self {
+        Self(Arc::new(Mutex::new(guard)))
+    }
+
+    pub(crate) fn take(&self) -> Option<InflightGuard> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).take()
+    }
+}
+
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        metrics::gauge!("morax_broker_inflight_requests").decrement(1);
+        self.tracker.inflight.fetch_sub(1, Ordering::SeqCst);
+        if self.tracker.draining.load(Ordering::SeqCst) {
+            self.tracker.completed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_report() {
+        let tracker = Arc::new(DrainTracker::default());
+
+        let finished = DrainTracker::try_enter(tracker.clone(), None).unwrap();
+        drop(finished);
+
+        let completed = DrainTracker::try_enter(tracker.clone(), None).unwrap();
+        let cancelled = DrainTracker::try_enter(tracker.clone(), None).unwrap();
+        tracker.record_split_flushed();
+
+        tracker.start_drain();
+        tracker.record_split_flushed();
+        drop(completed);
+
+        let report = tracker.report();
+        drop(cancelled);
+
+        assert_eq!(
+            report,
+            DrainReport {
+                requests_drained: 2,
+                requests_completed: 1,
+                splits_flushed: 1,
+                forced_cancellations: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_inflight_limit() {
+        let tracker = Arc::new(DrainTracker::default());
+        let limit = NonZeroUsize::new(2);
+
+        let first = DrainTracker::try_enter(tracker.clone(), limit).unwrap();
+        let _second = DrainTracker::try_enter(tracker.clone(), limit).unwrap();
+        assert!(DrainTracker::try_enter(tracker.clone(), limit).is_none());
+
+        drop(first);
+        let _third = DrainTracker::try_enter(tracker.clone(), limit).unwrap();
+        assert!(DrainTracker::try_enter(tracker.clone(), limit).is_none());
+    }
+
+    #[test]
+    fn test_inflight_slot() {
+        let tracker = Arc::new(DrainTracker::default());
+        let limit = NonZeroUsize::new(1);
+
+        // a guard taken out of the slot outlives it, as a streamed response does
+        let slot = InflightSlot::new(DrainTracker::try_enter(tracker.clone(), limit));
+        let taken = slot.take();
+        drop(slot);
+        assert!(DrainTracker::try_enter(tracker.clone(), limit).is_none());
+        drop(taken);
+
+        // a guard left in the slot is released with it
+        let slot = InflightSlot::new(DrainTracker::try_enter(tracker.clone(), limit));
+        drop(slot);
+        assert!(DrainTracker::try_enter(tracker.clone(), limit).is_some());
+    }
+}
@@ -16,6 +16,7 @@ use std::borrow::Borrow;
 
 use morax_protos::request::ErrorCode;
 use morax_protos::request::ErrorResponse;
+use morax_protos::request::LogEndOffset;
 use poem::http::StatusCode;
 use poem::IntoResponse;
 
@@ -25,6 +26,16 @@ pub(crate) struct ErrorWithCode {
 }
 
 impl ErrorWithCode {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> ErrorWithCode {
+        ErrorWithCode {
+            inner: ErrorResponse {
+                code,
+                message: message.into(),
+                end_offset: None,
+            },
+        }
+    }
+
     pub fn with_fallback_status<T, E>(code: ErrorCode) -> impl FnOnce(T) -> ErrorWithCode
     where
         T: Borrow<error_stack::Report<E>>,
@@ -33,8 +44,13 @@ impl ErrorWithCode {
             let err = err.borrow();
             let message = format!("{err:?}");
             let code = err.downcast_ref::<ErrorCode>().cloned().unwrap_or(code);
+            let end_offset = err.downcast_ref::<LogEndOffset>().map(|offset| offset.0);
             ErrorWithCode {
-                inner: ErrorResponse { code, message },
+                inner: ErrorResponse {
+                    code,
+                    message,
+                    end_offset,
+                },
             }
         }
     }
@@ -44,6 +60,14 @@ impl IntoResponse for ErrorWithCode {
     fn into_response(self) -> poem::Response {
         let status = match self.inner.code {
             ErrorCode::Unexpected => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::AlreadyExists => StatusCode::CONFLICT,
+            ErrorCode::InvalidArgument => StatusCode::BAD_REQUEST,
+            ErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::OutOfRange => StatusCode::RANGE_NOT_SATISFIABLE,
+            ErrorCode::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::OffsetConflict => StatusCode::CONFLICT,
+            ErrorCode::Sealed => StatusCode::CONFLICT,
         };
 
         let body =
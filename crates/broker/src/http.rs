@@ -12,31 +12,71 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
-use morax_meta::PostgresMetaService;
+use fastrace::collector::SpanContext;
+use fastrace::future::FutureExt;
+use futures::StreamExt;
+use morax_meta::MetaService;
+use morax_protos::config::BrokerConfig;
 use morax_protos::request::AppendLogRequest;
 use morax_protos::request::AppendLogResponse;
 use morax_protos::request::CreateLogRequest;
 use morax_protos::request::CreateLogResponse;
+use morax_protos::request::DeleteLogRequest;
+use morax_protos::request::DeleteLogResponse;
+use morax_protos::request::DescribeSplitResponse;
 use morax_protos::request::ErrorCode;
+use morax_protos::request::FollowLogRequest;
 use morax_protos::request::ReadLogRequest;
 use morax_protos::request::ReadLogResponse;
+use morax_protos::request::SealLogResponse;
+use morax_protos::request::TrimLogRequest;
+use morax_protos::request::TrimLogResponse;
+use morax_storage::StorageContext;
+use poem::http::HeaderValue;
+use poem::http::StatusCode;
 use poem::middleware::AddData;
 use poem::middleware::Compression;
 use poem::web::Data;
 use poem::web::Json;
+use poem::web::Path;
+use poem::Body;
+use poem::Endpoint;
 use poem::EndpointExt;
+use poem::IntoResponse;
 use poem::Route;
 
 use crate::broker::Broker;
+use crate::drain::InflightSlot;
 use crate::error::ErrorWithCode;
+use crate::DrainTracker;
 
 #[poem::handler]
 pub async fn health_check() -> poem::Result<String> {
     Ok("OK".to_string())
 }
 
+const DEFAULT_MAX_APPEND_ENTRIES: usize = 10000;
+
+/// Readiness probes fail fast rather than hang on an unresponsive dependency.
+const READY_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[poem::handler]
+pub async fn ready_check(Data(broker): Data<&Broker>) -> poem::Result<String> {
+    broker
+        .ready(READY_CHECK_TIMEOUT)
+        .await
+        .inspect_err(|err| log::warn!(err:?; "broker is not ready"))
+        .map_err(|err| {
+            poem::Error::from_string(err.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+        })?;
+    Ok("OK".to_string())
+}
+
 #[poem::handler]
 pub async fn create(
     Data(broker): Data<&Broker>,
@@ -50,6 +90,32 @@ pub async fn create(
     Ok(Json(response))
 }
 
+#[poem::handler]
+pub async fn delete(
+    Data(broker): Data<&Broker>,
+    Json(request): Json<DeleteLogRequest>,
+) -> poem::Result<Json<DeleteLogResponse>> {
+    let response = broker
+        .delete(request)
+        .await
+        .inspect_err(|err| log::error!(err:?; "failed to delete log"))
+        .map_err(ErrorWithCode::with_fallback_status(ErrorCode::Unexpected))?;
+    Ok(Json(response))
+}
+
+#[poem::handler]
+pub async fn trim(
+    Data(broker): Data<&Broker>,
+    Json(request): Json<TrimLogRequest>,
+) -> poem::Result<Json<TrimLogResponse>> {
+    let response = broker
+        .trim(request)
+        .await
+        .inspect_err(|err| log::error!(err:?; "failed to trim log"))
+        .map_err(ErrorWithCode::with_fallback_status(ErrorCode::Unexpected))?;
+    Ok(Json(response))
+}
+
 #[poem::handler]
 pub async fn read(
     Data(broker): Data<&Broker>,
@@ -63,6 +129,75 @@ pub async fn read(
     Ok(Json(response))
 }
 
+#[poem::handler]
+pub async fn follow(
+    Data(broker): Data<&Broker>,
+    Data(slot): Data<&InflightSlot>,
+    Json(request): Json<FollowLogRequest>,
+) -> poem::Result<poem::Response> {
+    let entries = broker
+        .follow(request)
+        .await
+        .inspect_err(|err| log::error!(err:?; "failed to follow log"))
+        .map_err(ErrorWithCode::with_fallback_status(ErrorCode::Unexpected))?;
+
+    // the request stays in flight until the stream ends
+    let guard = slot.take();
+    // once the response starts streaming, an error can only abort it
+    let lines = entries.map(move |entry| {
+        let _guard = &guard;
+        let entry = entry.map_err(|err| {
+            log::error!(err:?; "failed to follow log");
+            std::io::Error::other(err.to_string())
+        })?;
+        let mut line = serde_json::to_vec(&entry).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+        Ok::<_, std::io::Error>(line)
+    });
+    Ok(poem::Response::builder()
+        .content_type("application/x-ndjson")
+        .body(Body::from_bytes_stream(lines)))
+}
+
+#[poem::handler]
+pub async fn describe_split(
+    Data(broker): Data<&Broker>,
+    Path(split_id): Path<String>,
+) -> poem::Result<Json<DescribeSplitResponse>> {
+    let response = broker
+        .describe_split(split_id)
+        .await
+        .inspect_err(|err| log::error!(err:?; "failed to describe split"))
+        .map_err(ErrorWithCode::with_fallback_status(ErrorCode::Unexpected))?;
+    Ok(Json(response))
+}
+
+#[poem::handler]
+pub async fn seal(
+    Data(broker): Data<&Broker>,
+    Path(name): Path<String>,
+) -> poem::Result<Json<SealLogResponse>> {
+    let response = broker
+        .seal(name, true)
+        .await
+        .inspect_err(|err| log::error!(err:?; "failed to seal log"))
+        .map_err(ErrorWithCode::with_fallback_status(ErrorCode::Unexpected))?;
+    Ok(Json(response))
+}
+
+#[poem::handler]
+pub async fn unseal(
+    Data(broker): Data<&Broker>,
+    Path(name): Path<String>,
+) -> poem::Result<Json<SealLogResponse>> {
+    let response = broker
+        .seal(name, false)
+        .await
+        .inspect_err(|err| log::error!(err:?; "failed to unseal log"))
+        .map_err(ErrorWithCode::with_fallback_status(ErrorCode::Unexpected))?;
+    Ok(Json(response))
+}
+
 #[poem::handler]
 pub async fn append(
     Data(broker): Data<&Broker>,
@@ -76,16 +211,73 @@ pub async fn append(
     Ok(Json(response))
 }
 
-pub fn make_api_router(meta: Arc<PostgresMetaService>) -> Route {
-    let broker = Broker::new(meta);
+/// Whether the request path is one of the health probes, relative to the versioned route.
+fn is_probe(path: &str) -> bool {
+    matches!(path, "/health" | "/ready")
+}
+
+pub fn make_api_router(
+    config: &BrokerConfig,
+    meta: Arc<dyn MetaService>,
+    storage: StorageContext,
+    drain: Arc<DrainTracker>,
+) -> Route {
+    let max_append_entries = config
+        .max_append_entries
+        .map_or(DEFAULT_MAX_APPEND_ENTRIES, NonZeroUsize::get);
+    let max_inflight_requests = config.max_inflight_requests;
+    let broker = Broker::new(meta, storage, drain.clone(), max_append_entries);
 
     let v1_route = Route::new()
         .at("/health", poem::get(health_check))
+        .at("/ready", poem::get(ready_check))
         .at("/create", poem::post(create))
+        .at("/delete", poem::post(delete))
+        .at("/trim", poem::post(trim))
         .at("/read", poem::post(read))
         .at("/append", poem::post(append))
+        .at("/follow", poem::post(follow))
+        .at("/admin/splits/:split_id", poem::get(describe_split))
+        .at("/admin/logs/:name/seal", poem::post(seal))
+        .at("/admin/logs/:name/unseal", poem::post(unseal))
         .with(Compression::new())
-        .with(AddData::new(broker));
+        .with(AddData::new(broker))
+        .around(move |ep, mut req| {
+            let api = req.uri().path().to_string();
+            // probes must answer while the broker is saturated, and are not the work to drain
+            let guard = (!is_probe(&api))
+                .then(|| DrainTracker::try_enter(drain.clone(), max_inflight_requests));
+            let start = Instant::now();
+            // the trace id correlates the logs of a request, and is returned as its request id
+            let span_context = SpanContext::random();
+            let request_id = format!("{:032x}", span_context.trace_id.0);
+            let root = morax_telemetry::start_root_span(api.clone(), span_context);
+            async move {
+                let mut resp = match guard {
+                    Some(None) => {
+                        metrics::counter!("morax_broker_rejected_requests_total", "api" => api)
+                            .increment(1);
+                        let message = "too many requests in flight; retry later";
+                        ErrorWithCode::new(ErrorCode::Unavailable, message).into_response()
+                    }
+                    guard => {
+                        let slot = InflightSlot::new(guard.flatten());
+                        req.extensions_mut().insert(slot.clone());
+                        let resp = ep.get_response(req).in_span(root).await;
+                        drop(slot);
+                        metrics::counter!("morax_broker_requests_total", "api" => api.clone())
+                            .increment(1);
+                        metrics::histogram!("morax_broker_request_duration_seconds", "api" => api)
+                            .record(start.elapsed());
+                        resp
+                    }
+                };
+                if let Ok(request_id) = HeaderValue::from_str(&request_id) {
+                    resp.headers_mut().insert("x-request-id", request_id);
+                }
+                Ok(resp)
+            }
+        });
 
     Route::new().nest("v1", v1_route)
 }
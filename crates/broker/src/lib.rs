@@ -13,9 +13,14 @@
 // limitations under the License.
 
 mod broker;
+mod drain;
 mod error;
 mod http;
+mod notify;
+mod split;
 
+pub use drain::DrainReport;
+pub use drain::DrainTracker;
 pub use http::make_api_router;
 
 #[derive(Debug, thiserror::Error)]
@@ -0,0 +1,88 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+
+use tokio::sync::Notify;
+
+/// Wakes up the followers of a log when entries are appended to it on this broker.
+///
+/// Only the followers hold the notifies, so that a log is forgotten once no followers remain.
+#[derive(Debug, Default)]
+pub(crate) struct AppendNotifier {
+    notifies: Mutex<HashMap<String, Weak<Notify>>>,
+}
+
+impl AppendNotifier {
+    /// Returns the notify of the log; followers should enable its notified future before reading
+    /// the log so that no append is missed in between.
+    pub(crate) fn subscribe(&self, name: &str) -> Arc<Notify> {
+        let mut notifies = self.notifies.lock().unwrap();
+        if let Some(notify) = notifies.get(name).and_then(Weak::upgrade) {
+            return notify;
+        }
+        // sweep the logs no longer followed before tracking a new one
+        notifies.retain(|_, notify| notify.strong_count() > 0);
+        let notify = Arc::new(Notify::new());
+        notifies.insert(name.to_string(), Arc::downgrade(&notify));
+        notify
+    }
+
+    pub(crate) fn notify(&self, name: &str) {
+        let mut notifies = self.notifies.lock().unwrap();
+        match notifies.get(name).map(Weak::upgrade) {
+            Some(Some(notify)) => notify.notify_waiters(),
+            Some(None) => {
+                notifies.remove(name);
+            }
+            None => {}
+        }
+    }
+
+    pub(crate) fn remove(&self, name: &str) {
+        let mut notifies = self.notifies.lock().unwrap();
+        if let Some(notify) = notifies.remove(name).and_then(|notify| notify.upgrade()) {
+            notify.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forget_logs_without_followers() {
+        let notifier = AppendNotifier::default();
+
+        let first = notifier.subscribe("log");
+        let second = notifier.subscribe("log");
+        assert!(Arc::ptr_eq(&first, &second));
+        drop(first);
+        notifier.notify("log");
+        assert_eq!(notifier.notifies.lock().unwrap().len(), 1);
+
+        drop(second);
+        notifier.notify("log");
+        assert!(notifier.notifies.lock().unwrap().is_empty());
+
+        // a log followed once is swept when another log is followed
+        drop(notifier.subscribe("log"));
+        let _other = notifier.subscribe("other");
+        assert_eq!(notifier.notifies.lock().unwrap().len(), 1);
+    }
+}
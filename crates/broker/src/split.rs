@@ -0,0 +1,234 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The format of the split objects written to the storage.
+//!
+//! A split starts with [`SPLIT_MAGIC`] and a format version, followed by the number of entries and
+//! each entry prefixed by its length; all integers are little-endian `u32`s. Entries are framed
+//! independently so that reading from an offset in the middle of a split skips the preceding
+//! entries without decoding them, and a reader that has read enough leaves the following entries
+//! undecoded.
+//!
+//! Splits written before the format was versioned are flexbuffers-serialized [`EntryData`] lists.
+//! They are still readable, and are told apart from versioned splits by the magic.
+
+use error_stack::bail;
+use error_stack::Report;
+use error_stack::Result;
+use error_stack::ResultExt;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::BrokerError;
+
+const SPLIT_MAGIC: &[u8; 4] = b"\xffMRX";
+const SPLIT_FORMAT_V1: u8 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EntryData {
+    data: Vec<u8>,
+}
+
+pub(crate) fn encode_split(entries: &[Vec<u8>]) -> Vec<u8> {
+    let size = entries.iter().map(|entry| 4 + entry.len()).sum::<usize>();
+    let mut buf = Vec::with_capacity(SPLIT_MAGIC.len() + 1 + 4 + size);
+    buf.extend_from_slice(SPLIT_MAGIC);
+    buf.push(SPLIT_FORMAT_V1);
+    buf.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for entry in entries {
+        buf.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        buf.extend_from_slice(entry);
+    }
+    buf
+}
+
+/// Decodes the entries of a split lazily, except for the first `skip` ones. Entries after the ones
+/// taken from the returned iterator are not read at all.
+pub(crate) fn decode_split(data: &[u8], skip: usize) -> Result<SplitEntries<'_>, BrokerError> {
+    let Some(data) = data.strip_prefix(SPLIT_MAGIC) else {
+        let mut entries = decode_flexbuffers_split(data)?;
+        let entries = entries.split_off(skip.min(entries.len()));
+        return Ok(SplitEntries::Flexbuffers(entries.into_iter()));
+    };
+
+    let make_error = || BrokerError("malformed split".to_string());
+    let mut reader = SplitReader { data };
+    match reader.read_u8().ok_or_else(make_error)? {
+        SPLIT_FORMAT_V1 => {}
+        version => bail!(BrokerError(format!(
+            "unknown split format version: {version}"
+        ))),
+    }
+
+    let count = reader.read_u32().ok_or_else(make_error)? as usize;
+    for _ in 0..skip.min(count) {
+        let len = reader.read_u32().ok_or_else(make_error)? as usize;
+        reader.read_bytes(len).ok_or_else(make_error)?;
+    }
+    Ok(SplitEntries::V1 {
+        reader,
+        remaining: count.saturating_sub(skip),
+    })
+}
+
+/// The entries of a split, decoded one by one as they are taken.
+pub(crate) enum SplitEntries<'a> {
+    V1 {
+        reader: SplitReader<'a>,
+        remaining: usize,
+    },
+    Flexbuffers(std::vec::IntoIter<Vec<u8>>),
+}
+
+impl Iterator for SplitEntries<'_> {
+    type Item = Result<Vec<u8>, BrokerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SplitEntries::V1 { reader, remaining } => {
+                if *remaining == 0 {
+                    return None;
+                }
+                *remaining -= 1;
+                let entry = reader
+                    .read_u32()
+                    .and_then(|len| reader.read_bytes(len as usize));
+                let Some(entry) = entry else {
+                    // no more entries can be told apart after a malformed one
+                    *remaining = 0;
+                    return Some(Err(Report::new(BrokerError("malformed split".to_string()))));
+                };
+                Some(Ok(entry.to_vec()))
+            }
+            SplitEntries::Flexbuffers(entries) => entries.next().map(Ok),
+        }
+    }
+}
+
+/// Returns the format of a split and the number of entries in it, without decoding the entries
+/// where the format allows.
+pub(crate) fn describe_split(data: &[u8]) -> Result<(&'static str, usize), BrokerError> {
+    let Some(data) = data.strip_prefix(SPLIT_MAGIC) else {
+        let entries = decode_flexbuffers_split(data)?;
+        return Ok(("flexbuffers", entries.len()));
+    };
+
+    let make_error = || BrokerError("malformed split".to_string());
+    let mut reader = SplitReader { data };
+    match reader.read_u8().ok_or_else(make_error)? {
+        SPLIT_FORMAT_V1 => {
+            let count = reader.read_u32().ok_or_else(make_error)?;
+            Ok(("v1", count as usize))
+        }
+        version => bail!(BrokerError(format!(
+            "unknown split format version: {version}"
+        ))),
+    }
+}
+
+fn decode_flexbuffers_split(data: &[u8]) -> Result<Vec<Vec<u8>>, BrokerError> {
+    let make_error = || BrokerError("failed to deserialize entry data".to_string());
+    let deserializer = flexbuffers::Reader::get_root(data).change_context_lazy(make_error)?;
+    let entries = Vec::<EntryData>::deserialize(deserializer).change_context_lazy(make_error)?;
+    Ok(entries.into_iter().map(|e| e.data).collect())
+}
+
+pub(crate) struct SplitReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SplitReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        let bytes = self.read_bytes(4)?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entries() -> Vec<Vec<u8>> {
+        vec![b"0".to_vec(), vec![], b"22".to_vec()]
+    }
+
+    fn decode_all(data: &[u8], skip: usize) -> Result<Vec<Vec<u8>>, BrokerError> {
+        decode_split(data, skip)?.collect()
+    }
+
+    #[test]
+    fn test_split_roundtrip() {
+        let split = encode_split(&make_entries());
+        assert_eq!(decode_all(&split, 0).unwrap(), make_entries());
+        assert_eq!(decode_all(&split, 2).unwrap(), vec![b"22".to_vec()]);
+        assert!(decode_all(&split, 3).unwrap().is_empty());
+
+        let truncated = &split[..split.len() - 1];
+        assert!(decode_all(truncated, 0).is_err());
+
+        assert_eq!(describe_split(&split).unwrap(), ("v1", 3));
+    }
+
+    #[test]
+    fn test_decode_malformed_count() {
+        let mut split = encode_split(&make_entries());
+        let count = SPLIT_MAGIC.len() + 1;
+        split[count..count + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_all(&split, 0).is_err());
+    }
+
+    #[test]
+    fn test_decode_split_lazily() {
+        let split = encode_split(&make_entries());
+        let mut entries = decode_split(&split, 1).unwrap();
+        assert_eq!(entries.next().unwrap().unwrap(), Vec::<u8>::new());
+
+        // the entries after the ones taken are never read
+        let truncated = &split[..split.len() - 1];
+        let entries = decode_split(truncated, 0).unwrap();
+        let entries = entries.take(2).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, make_entries()[..2]);
+        let mut entries = decode_split(truncated, 2).unwrap();
+        assert!(entries.next().unwrap().is_err());
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn test_read_flexbuffers_split() {
+        let entries = make_entries()
+            .into_iter()
+            .map(|data| EntryData { data })
+            .collect::<Vec<_>>();
+        let mut serializer = flexbuffers::FlexbufferSerializer::new();
+        entries.serialize(&mut serializer).unwrap();
+        let split = serializer.take_buffer();
+
+        assert_eq!(decode_all(&split, 0).unwrap(), make_entries());
+        assert_eq!(decode_all(&split, 1).unwrap(), make_entries()[1..]);
+        assert_eq!(describe_split(&split).unwrap(), ("flexbuffers", 3));
+    }
+}
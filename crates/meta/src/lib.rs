@@ -12,13 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use migrate::LATEST_META_VERSION;
 pub use model::*;
 pub use service::PostgresMetaService;
+pub use traits::MetaService;
 
-mod bootstrap;
+mod migrate;
 mod model;
 mod service;
+mod traits;
 
 #[derive(Debug, thiserror::Error)]
 #[error("{0}")]
-pub struct MetaError(String);
+pub struct MetaError(pub String);
+
+/// Attached to the error of a commit that may have been applied despite the error, e.g., the
+/// connection broke after the commit was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitIndeterminate;
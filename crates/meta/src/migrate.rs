@@ -0,0 +1,94 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sqlx::Executor;
+use sqlx::PgPool;
+
+/// The meta version that this build of Morax runs against.
+pub const LATEST_META_VERSION: i32 = MIGRATIONS.len() as i32;
+
+/// Schema migrations in order; the migration at index `i` upgrades the meta database from
+/// version `i` to version `i + 1`.
+///
+/// Each statement must be idempotent, so that a migration interrupted or raced by another broker
+/// can safely be applied again. Never change a released migration; append a new one instead.
+const MIGRATIONS: &[&[&str]] = &[
+    // v1: topics and their splits
+    &[
+        // create a sequence for producer ids
+        "CREATE SEQUENCE IF NOT EXISTS producer_ids CYCLE",
+        // topics
+        r#"
+CREATE TABLE IF NOT EXISTS topics (
+    id UUID NOT NULL,
+    name TEXT NOT NULL,
+    properties JSONB NOT NULL,
+    UNIQUE (id), UNIQUE (name)
+);
+"#,
+        r#"
+CREATE TABLE IF NOT EXISTS topic_offsets (
+    topic_id UUID NOT NULL,
+    last_offset BIGINT NOT NULL,
+    UNIQUE (topic_id)
+);
+"#,
+        // topic splits:
+        // * start_offset is inclusive
+        // * end_offset is exclusive
+        r#"
+CREATE TABLE IF NOT EXISTS topic_splits (
+    topic_id UUID NOT NULL,
+    topic_name TEXT NOT NULL,
+    start_offset BIGINT NOT NULL,
+    end_offset BIGINT NOT NULL,
+    split_id TEXT NOT NULL
+);
+"#,
+    ],
+    // v2: split ids are the idempotency key of committing record batches
+    &["CREATE UNIQUE INDEX IF NOT EXISTS topic_splits_split_id ON topic_splits (split_id);"],
+    // v3: commit time of splits for retention; clock_timestamp() rather than now() so that it
+    // follows the offsets order of splits committed by concurrent transactions
+    &["ALTER TABLE topic_splits ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp();"],
+    // v4: the first offset readable after the log is trimmed; offsets below it are out of range
+    &["ALTER TABLE topic_offsets ADD COLUMN IF NOT EXISTS start_offset BIGINT NOT NULL DEFAULT 0;"],
+    // v5: sealed topics reject appends but can still be read; kept along with the offsets so that
+    // it is checked under the same lock as committing splits
+    &["ALTER TABLE topic_offsets ADD COLUMN IF NOT EXISTS sealed BOOLEAN NOT NULL DEFAULT false;"],
+];
+
+/// Applies all the migrations after `current_version`, each in its own transaction that also
+/// records the version it upgrades to.
+pub async fn migrate(pool: PgPool, current_version: i32) -> error_stack::Result<(), sqlx::Error> {
+    for (version, statements) in (1..).zip(MIGRATIONS) {
+        if version <= current_version {
+            continue;
+        }
+
+        log::info!("migrating meta database to version {version}");
+        let mut txn = pool.begin().await?;
+        txn.execute("CREATE TABLE IF NOT EXISTS meta_version(version INT NOT NULL PRIMARY KEY);")
+            .await?;
+        for statement in statements.iter() {
+            txn.execute(*statement).await?;
+        }
+        sqlx::query("INSERT INTO meta_version (version) VALUES ($1) ON CONFLICT DO NOTHING;")
+            .bind(version)
+            .execute(&mut *txn)
+            .await?;
+        txn.commit().await?;
+    }
+    Ok(())
+}
@@ -20,6 +20,8 @@ use uuid::Uuid;
 pub struct CreateTopicRequest {
     pub name: String,
     pub properties: TopicProps,
+    /// Whether to return the existing topic if one with the same name and properties exists.
+    pub if_not_exists: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +29,8 @@ pub struct CommitRecordBatchesRequest {
     pub topic_name: String,
     pub record_len: i32,
     pub split_id: String,
+    /// Commits the split only if the last offset of the topic equals this offset.
+    pub expected_offset: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,66 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Injects the faults configured for testing into the meta operations, so that tests can exercise
+//! how the failures are handled without an unreliable database.
+
+use morax_protos::config::MetaFault;
+use morax_protos::config::MetaOperation;
+
+use crate::service::MetaResult;
+use crate::MetaError;
+use crate::PostgresMetaService;
+
+impl PostgresMetaService {
+    /// Fails the operation before it reaches the database if a fault of it fires.
+    pub(crate) fn inject_fault(&self, operation: MetaOperation) -> MetaResult<()> {
+        if self.faults.iter().any(|fault| fires(fault, operation)) {
+            return Err(MetaError(format!("injected meta fault to {operation:?}")).into());
+        }
+        Ok(())
+    }
+}
+
+fn fires(fault: &MetaFault, operation: MetaOperation) -> bool {
+    if operation != fault.operation {
+        return false;
+    }
+    if fault.ratio >= 1.0 {
+        return true;
+    }
+    // UUIDs v4 are random enough for deciding a failure
+    let (random, _) = uuid::Uuid::new_v4().as_u64_pair();
+    (random as f64) < fault.ratio * u64::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_by_operation_and_ratio() {
+        let fault = MetaFault {
+            operation: MetaOperation::Commit,
+            ratio: 1.0,
+        };
+        assert!(fires(&fault, MetaOperation::Commit));
+        assert!(!fires(&fault, MetaOperation::Fetch));
+
+        let fault = MetaFault {
+            ratio: 0.0,
+            ..fault
+        };
+        assert!(!fires(&fault, MetaOperation::Commit));
+    }
+}
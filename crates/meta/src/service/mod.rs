@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use error_stack::bail;
 use error_stack::ResultExt;
 use morax_protos::config::MetaServiceConfig;
@@ -22,16 +24,37 @@ use sqlx::PgConnection;
 use sqlx::PgPool;
 use sqlx::Postgres;
 
-use crate::bootstrap::bootstrap;
+use crate::migrate::migrate;
+use crate::migrate::LATEST_META_VERSION;
 use crate::MetaError;
 
+#[cfg(any(test, feature = "fault-injection"))]
+mod fault;
 mod pubsub;
 mod topic;
 
 type MetaResult<T> = error_stack::Result<T, MetaError>;
 
-async fn connect(url: &str) -> error_stack::Result<PgPool, sqlx::Error> {
-    Ok(PgPoolOptions::new().connect(url).await?)
+async fn connect(config: &MetaServiceConfig) -> error_stack::Result<PgPool, sqlx::Error> {
+    let max_connections = config
+        .max_connections
+        .unwrap_or_else(|| morax_runtime::num_cpus().get() as u32 * 2);
+    let min_connections = config.min_connections.unwrap_or(0);
+    let acquire_timeout = Duration::from_millis(config.acquire_timeout_ms.unwrap_or(30_000));
+    let idle_timeout = Duration::from_millis(config.idle_timeout_ms.unwrap_or(600_000));
+    log::info!(
+        max_connections, min_connections, acquire_timeout:?, idle_timeout:?;
+        "configuring meta database connection pool"
+    );
+
+    let pool = PgPoolOptions::new()
+        .max_connections(max_connections)
+        .min_connections(min_connections)
+        .acquire_timeout(acquire_timeout)
+        .idle_timeout(idle_timeout)
+        .connect(&config.service_url)
+        .await?;
+    Ok(pool)
 }
 
 async fn resolve_meta_version(url: &str) -> error_stack::Result<i32, sqlx::Error> {
@@ -61,20 +84,25 @@ async fn resolve_meta_version(url: &str) -> error_stack::Result<i32, sqlx::Error
 #[derive(Debug)]
 pub struct PostgresMetaService {
     pool: PgPool,
+    commit_retry_max_times: usize,
+    #[cfg(any(test, feature = "fault-injection"))]
+    faults: Vec<morax_protos::config::MetaFault>,
 }
 
 impl PostgresMetaService {
     pub async fn new(config: &MetaServiceConfig) -> MetaResult<Self> {
-        let make_error = || MetaError("failed to connect and bootstrap the database".to_string());
+        let make_error = || MetaError("failed to connect and migrate the database".to_string());
 
         let url = config.service_url.as_str();
-        log::info!("connecting to meta service at {url}");
+        let commit_retry_max_times = config.commit_retry_max_times.unwrap_or(3);
+        let redacted_url = config.redacted().service_url;
+        log::info!("connecting to meta service at {redacted_url}");
 
         if !Postgres::database_exists(url)
             .await
             .change_context_lazy(make_error)?
         {
-            log::info!("creating meta database at {url}");
+            log::info!("creating meta database at {redacted_url}");
             Postgres::create_database(url)
                 .await
                 .change_context_lazy(make_error)?;
@@ -85,21 +113,43 @@ impl PostgresMetaService {
             .change_context_lazy(make_error)?;
         log::info!("resolved meta version: {meta_version}");
 
-        match meta_version {
-            0 => {
-                log::info!("bootstrapping meta database at {url}");
-                let pool = connect(url).await.change_context_lazy(make_error)?;
-                bootstrap(pool.clone())
-                    .await
-                    .change_context_lazy(make_error)?;
-                Ok(Self { pool })
-            }
-            1 => {
-                log::info!("using existing meta database at {url}");
-                let pool = connect(url).await.change_context_lazy(make_error)?;
-                Ok(Self { pool })
-            }
-            version => bail!(MetaError(format!("unsupported meta version: {version}"))),
+        if meta_version > LATEST_META_VERSION {
+            bail!(MetaError(format!(
+                "unsupported meta version: {meta_version}; \
+                this build of Morax supports up to version {LATEST_META_VERSION}"
+            )));
+        }
+
+        let pool = connect(config).await.change_context_lazy(make_error)?;
+        if meta_version < LATEST_META_VERSION {
+            log::info!(
+                "migrating meta database at {redacted_url} \
+                from version {meta_version} to {LATEST_META_VERSION}"
+            );
+            migrate(pool.clone(), meta_version)
+                .await
+                .change_context_lazy(make_error)?;
+        } else {
+            log::info!("using existing meta database at {redacted_url}");
         }
+
+        Ok(Self {
+            pool,
+            commit_retry_max_times,
+            #[cfg(any(test, feature = "fault-injection"))]
+            faults: config.faults.clone(),
+        })
+    }
+
+    /// Checks that the meta database answers queries.
+    pub async fn ping(&self) -> MetaResult<()> {
+        let make_error = || MetaError("failed to ping the database".to_string());
+        let pool = self.pool.clone();
+
+        sqlx::query("SELECT 1")
+            .execute(&pool)
+            .await
+            .change_context_lazy(make_error)?;
+        Ok(())
     }
 }
@@ -12,9 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use backon::ExponentialBuilder;
+use backon::Retryable;
+use error_stack::Report;
 use error_stack::ResultExt;
+#[cfg(any(test, feature = "fault-injection"))]
+use morax_protos::config::MetaOperation;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::LogEndOffset;
+use sqlx::Postgres;
+use sqlx::Transaction;
 
 use crate::service::MetaResult;
+use crate::CommitIndeterminate;
 use crate::CommitRecordBatchesRequest;
 use crate::FetchRecordBatchesRequest;
 use crate::MetaError;
@@ -32,11 +45,15 @@ impl PostgresMetaService {
             .change_context_lazy(make_error)
     }
 
+    #[fastrace::trace]
     pub async fn fetch_record_batches(
         &self,
         request: FetchRecordBatchesRequest,
     ) -> MetaResult<Vec<TopicSplit>> {
         let make_error = || MetaError("failed to fetch record batches".to_string());
+        #[cfg(any(test, feature = "fault-injection"))]
+        self.inject_fault(MetaOperation::Fetch)
+            .change_context_lazy(make_error)?;
         let pool = self.pool.clone();
 
         let topic_id = if request.topic_id != uuid::Uuid::default() {
@@ -49,6 +66,30 @@ impl PostgresMetaService {
                 .change_context_lazy(make_error)?
         };
 
+        let (start_offset, last_offset): (i64, i64) = sqlx::query_as(
+            "SELECT start_offset, last_offset FROM topic_offsets WHERE topic_id = $1",
+        )
+        .bind(topic_id)
+        .fetch_one(&pool)
+        .await
+        .change_context_lazy(make_error)?;
+        if request.offset < start_offset {
+            return Err(Report::new(make_error())
+                .attach_printable(format!(
+                    "offset {} is below the start offset {start_offset} of the trimmed topic",
+                    request.offset
+                ))
+                .attach(ErrorCode::OutOfRange));
+        }
+        if request.offset > last_offset {
+            return Err(Report::new(make_error())
+                .attach_printable(format!(
+                    "offset {} is beyond the end offset {last_offset} of the topic",
+                    request.offset
+                ))
+                .attach(ErrorCode::OutOfRange));
+        }
+
         sqlx::query_as("SELECT topic_id, topic_name, start_offset, end_offset, split_id FROM topic_splits WHERE topic_id = $1 AND end_offset > $2 ORDER BY end_offset ASC")
             .bind(topic_id)
             .bind(request.offset)
@@ -57,51 +98,183 @@ impl PostgresMetaService {
             .change_context_lazy(make_error)
     }
 
+    /// Commits the record batches written to the split of `request.split_id`, and returns the
+    /// offsets range assigned to the split.
+    ///
+    /// The split id is the idempotency key: committing a split that has been committed returns the
+    /// offsets range assigned before, so that transient errors can be retried safely.
+    ///
+    /// The split is not committed if the topic is sealed, or if `request.expected_offset` is set
+    /// and does not equal the last offset of the topic, in which case the error carries the last
+    /// offset as [`LogEndOffset`].
+    ///
+    /// If the commit still fails transiently after the retries, the error carries
+    /// [`ErrorCode::Unavailable`]. If any attempt failed once it started to apply the commit, the
+    /// commit may have been applied, and the error carries [`CommitIndeterminate`].
+    #[fastrace::trace]
     pub async fn commit_record_batches(
         &self,
         request: CommitRecordBatchesRequest,
     ) -> MetaResult<(i64, i64)> {
         let make_error = || MetaError("failed to commit record batches".to_string());
+        #[cfg(any(test, feature = "fault-injection"))]
+        self.inject_fault(MetaOperation::Commit)
+            .change_context_lazy(make_error)?;
+
+        let backoff = ExponentialBuilder::default()
+            .with_max_times(self.commit_retry_max_times)
+            .with_jitter();
+
+        // set once an attempt fails after it started to apply the commit; a later attempt that
+        // fails before does not tell whether the earlier one has been applied
+        let indeterminate = AtomicBool::new(false);
+        let committed = (|| self.do_commit_record_batches(request.clone(), &indeterminate))
+            .retry(backoff)
+            .when(|err| is_transient_error(err.current_context()))
+            .notify(|err, dur| {
+                log::warn!(err:?; "retrying to commit record batches after {dur:?}");
+            })
+            .await
+            .map_err(|err| {
+                let transient = is_transient_error(err.current_context());
+                let mut err = err.change_context(make_error());
+                if transient {
+                    err = err.attach(ErrorCode::Unavailable);
+                }
+                if indeterminate.load(Ordering::Relaxed) {
+                    err = err.attach(CommitIndeterminate);
+                }
+                err
+            })?;
+
+        committed.map_err(|rejection| match rejection {
+            CommitRejection::NotFound => Report::new(make_error())
+                .attach_printable(format!("topic {} not found", request.topic_name))
+                .attach(ErrorCode::NotFound),
+            CommitRejection::Sealed => Report::new(make_error())
+                .attach_printable(format!("topic {} is sealed", request.topic_name))
+                .attach(ErrorCode::Sealed),
+            CommitRejection::OffsetConflict(last_offset) => {
+                let expected_offset = request.expected_offset.unwrap_or_default();
+                Report::new(make_error())
+                    .attach_printable(format!(
+                        "expected end offset {expected_offset} but the end offset is {last_offset}"
+                    ))
+                    .attach(LogEndOffset(last_offset))
+                    .attach(ErrorCode::OffsetConflict)
+            }
+        })
+    }
+
+    async fn do_commit_record_batches(
+        &self,
+        request: CommitRecordBatchesRequest,
+        indeterminate: &AtomicBool,
+    ) -> error_stack::Result<Result<(i64, i64), CommitRejection>, sqlx::Error> {
         let pool = self.pool.clone();
 
-        let mut txn = pool.begin().await.change_context_lazy(make_error)?;
+        let mut txn = pool.begin().await?;
 
-        let (topic_id, topic_name): (uuid::Uuid, String) =
+        let topic: Option<(uuid::Uuid, String)> =
             sqlx::query_as("SELECT id, name FROM topics WHERE name = $1")
-                .bind(request.topic_name)
-                .fetch_one(&mut *txn)
-                .await
-                .change_context_lazy(make_error)?;
+                .bind(&request.topic_name)
+                .fetch_optional(&mut *txn)
+                .await?;
+        let Some((topic_id, topic_name)) = topic else {
+            return Ok(Err(CommitRejection::NotFound));
+        };
 
-        let start_offset: i64 = sqlx::query_scalar(
-            "SELECT last_offset FROM topic_offsets WHERE topic_id = $1 FOR UPDATE",
+        let (start_offset, sealed): (i64, bool) = sqlx::query_as(
+            "SELECT last_offset, sealed FROM topic_offsets WHERE topic_id = $1 FOR UPDATE",
         )
         .bind(topic_id)
         .fetch_one(&mut *txn)
-        .await
-        .change_context_lazy(make_error)?;
-        let last_offset = start_offset + request.record_len as i64;
-        let end_offset = sqlx::query_scalar(
-            "UPDATE topic_offsets SET last_offset = $1 WHERE topic_id = $2 RETURNING last_offset",
+        .await?;
+
+        // checked after locking the topic offsets so that concurrent commits of the same split
+        // observe each other
+        let committed: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT start_offset, end_offset FROM topic_splits WHERE topic_id = $1 AND split_id = $2",
         )
-        .bind(last_offset)
         .bind(topic_id)
-        .fetch_one(&mut *txn)
-        .await
-        .change_context_lazy(make_error)?;
-        debug_assert_eq!(last_offset, end_offset, "last offset mismatch");
+        .bind(&request.split_id)
+        .fetch_optional(&mut *txn)
+        .await?;
+        if let Some(offsets) = committed {
+            return Ok(Ok(offsets));
+        }
 
-        sqlx::query("INSERT INTO topic_splits (topic_id, topic_name, start_offset, end_offset, split_id) VALUES ($1, $2, $3, $4, $5)")
-            .bind(topic_id)
-            .bind(topic_name)
-            .bind(start_offset)
-            .bind(end_offset)
-            .bind(request.split_id)
-            .execute(&mut *txn)
-            .await
-            .change_context_lazy(make_error)?;
+        if sealed {
+            return Ok(Err(CommitRejection::Sealed));
+        }
+
+        // compared under the lock of the topic offsets, so that exactly one of the concurrent
+        // appends expecting the same offset commits
+        if let Some(expected_offset) = request.expected_offset {
+            if expected_offset != start_offset {
+                return Ok(Err(CommitRejection::OffsetConflict(start_offset)));
+            }
+        }
+
+        let last_offset = start_offset + request.record_len as i64;
+        let split = TopicSplit {
+            topic_id,
+            topic_name,
+            start_offset,
+            end_offset: last_offset,
+            split_id: request.split_id,
+        };
+        let applied = apply_commit(txn, split).await;
+        if applied.is_err() {
+            indeterminate.store(true, Ordering::Relaxed);
+        }
+        applied.map(Ok)
+    }
+}
+
+/// Moves the last offset of the topic past the split and records the split; once this starts, a
+/// failure does not tell whether the commit has been applied.
+async fn apply_commit(
+    mut txn: Transaction<'_, Postgres>,
+    split: TopicSplit,
+) -> error_stack::Result<(i64, i64), sqlx::Error> {
+    let end_offset = sqlx::query_scalar(
+        "UPDATE topic_offsets SET last_offset = $1 WHERE topic_id = $2 RETURNING last_offset",
+    )
+    .bind(split.end_offset)
+    .bind(split.topic_id)
+    .fetch_one(&mut *txn)
+    .await?;
+    debug_assert_eq!(split.end_offset, end_offset, "last offset mismatch");
+
+    sqlx::query("INSERT INTO topic_splits (topic_id, topic_name, start_offset, end_offset, split_id) VALUES ($1, $2, $3, $4, $5)")
+        .bind(split.topic_id)
+        .bind(split.topic_name)
+        .bind(split.start_offset)
+        .bind(split.end_offset)
+        .bind(split.split_id)
+        .execute(&mut *txn)
+        .await?;
+
+    txn.commit().await?;
+    Ok((split.start_offset, split.end_offset))
+}
+
+/// Why a split is not committed, other than failing to access the database.
+enum CommitRejection {
+    /// The topic does not exist.
+    NotFound,
+    /// The topic is sealed to appends.
+    Sealed,
+    /// The last offset of the topic does not match the expected offset.
+    OffsetConflict(i64),
+}
 
-        txn.commit().await.change_context_lazy(make_error)?;
-        Ok((start_offset, end_offset))
+fn is_transient_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        // serialization_failure and deadlock_detected
+        sqlx::Error::Database(err) => matches!(err.code().as_deref(), Some("40001" | "40P01")),
+        _ => false,
     }
 }
@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
+use error_stack::Report;
 use error_stack::ResultExt;
+use morax_protos::request::ErrorCode;
 use sqlx::types::Json;
 
 use crate::service::MetaResult;
@@ -20,8 +24,10 @@ use crate::CreateTopicRequest;
 use crate::MetaError;
 use crate::PostgresMetaService;
 use crate::Topic;
+use crate::TopicSplit;
 
 impl PostgresMetaService {
+    #[fastrace::trace]
     pub async fn create_topic(&self, request: CreateTopicRequest) -> MetaResult<Topic> {
         let make_error = || MetaError("failed to create topic".to_string());
         let pool = self.pool.clone();
@@ -32,14 +38,48 @@ impl PostgresMetaService {
         let topic_name = request.name;
         let properties = request.properties;
 
-        let topic = sqlx::query_as("INSERT INTO topics (id, name, properties) VALUES ($1, $2, $3) RETURNING id, name, properties")
+        // concurrent creators of the same topic wait for each other on the unique name, and all
+        // but the first one find the row already exists
+        let topic: Option<Topic> = sqlx::query_as("INSERT INTO topics (id, name, properties) VALUES ($1, $2, $3) ON CONFLICT (name) DO NOTHING RETURNING id, name, properties")
             .bind(topic_id)
-            .bind(topic_name)
-            .bind(Json(properties))
-            .fetch_one(&mut *txn)
+            .bind(&topic_name)
+            .bind(Json(&properties))
+            .fetch_optional(&mut *txn)
             .await
             .change_context_lazy(make_error)?;
 
+        let Some(topic) = topic else {
+            if !request.if_not_exists {
+                return Err(
+                    Report::new(MetaError(format!("topic already exists: {topic_name}")))
+                        .attach(ErrorCode::AlreadyExists),
+                );
+            }
+            let topic: Topic =
+                sqlx::query_as("SELECT id, name, properties FROM topics WHERE name = $1")
+                    .bind(&topic_name)
+                    .fetch_one(&mut *txn)
+                    .await
+                    .change_context_lazy(make_error)?;
+
+            // re-applying the same definition succeeds, but a conflicting one is not masked;
+            // compared as JSONB so that the order of keys does not matter
+            let same_properties: bool =
+                sqlx::query_scalar("SELECT properties = $2 FROM topics WHERE id = $1")
+                    .bind(topic.id)
+                    .bind(Json(&properties))
+                    .fetch_one(&mut *txn)
+                    .await
+                    .change_context_lazy(make_error)?;
+            if !same_properties {
+                return Err(Report::new(MetaError(format!(
+                    "topic already exists with different properties: {topic_name}"
+                )))
+                .attach(ErrorCode::AlreadyExists));
+            }
+            return Ok(topic);
+        };
+
         sqlx::query("INSERT INTO topic_offsets (topic_id, last_offset) VALUES ($1, 0)")
             .bind(topic_id)
             .execute(&mut *txn)
@@ -50,6 +90,46 @@ impl PostgresMetaService {
         Ok(topic)
     }
 
+    /// Deletes the topic along with its offsets and splits metadata.
+    ///
+    /// Returns the deleted topic and its splits so that the caller can clean up the split objects,
+    /// or `None` if the topic does not exist.
+    #[fastrace::trace]
+    pub async fn delete_topic(
+        &self,
+        topic_name: String,
+    ) -> MetaResult<Option<(Topic, Vec<TopicSplit>)>> {
+        let make_error = || MetaError("failed to delete topic".to_string());
+        let pool = self.pool.clone();
+
+        let mut txn = pool.begin().await.change_context_lazy(make_error)?;
+
+        let topic: Option<Topic> =
+            sqlx::query_as("DELETE FROM topics WHERE name = $1 RETURNING id, name, properties")
+                .bind(&topic_name)
+                .fetch_optional(&mut *txn)
+                .await
+                .change_context_lazy(make_error)?;
+        let Some(topic) = topic else {
+            return Ok(None);
+        };
+
+        sqlx::query("DELETE FROM topic_offsets WHERE topic_id = $1")
+            .bind(topic.id)
+            .execute(&mut *txn)
+            .await
+            .change_context_lazy(make_error)?;
+
+        let splits = sqlx::query_as("DELETE FROM topic_splits WHERE topic_id = $1 RETURNING topic_id, topic_name, start_offset, end_offset, split_id")
+            .bind(topic.id)
+            .fetch_all(&mut *txn)
+            .await
+            .change_context_lazy(make_error)?;
+
+        txn.commit().await.change_context_lazy(make_error)?;
+        Ok(Some((topic, splits)))
+    }
+
     pub async fn get_topics_by_id(&self, topic_id: uuid::Uuid) -> MetaResult<Topic> {
         let make_error = || MetaError("failed to get all topics".to_string());
         let pool = self.pool.clone();
@@ -61,15 +141,20 @@ impl PostgresMetaService {
             .change_context_lazy(make_error)
     }
 
+    #[fastrace::trace]
     pub async fn get_topics_by_name(&self, topic_name: String) -> MetaResult<Topic> {
         let make_error = || MetaError("failed to get all topics".to_string());
         let pool = self.pool.clone();
 
-        sqlx::query_as("SELECT id, name, properties FROM topics WHERE name = $1")
+        let topic = sqlx::query_as("SELECT id, name, properties FROM topics WHERE name = $1")
             .bind(&topic_name)
-            .fetch_one(&pool)
+            .fetch_optional(&pool)
             .await
-            .change_context_lazy(make_error)
+            .change_context_lazy(make_error)?;
+        topic.ok_or_else(|| {
+            Report::new(MetaError(format!("topic not found: {topic_name}")))
+                .attach(ErrorCode::NotFound)
+        })
     }
 
     pub async fn get_all_topics(&self) -> MetaResult<Vec<Topic>> {
@@ -81,4 +166,174 @@ impl PostgresMetaService {
             .await
             .change_context_lazy(make_error)
     }
+
+    /// Returns the splits of the topic committed more than `retention` ago, in offsets order.
+    #[fastrace::trace]
+    pub async fn fetch_expired_splits(
+        &self,
+        topic_id: uuid::Uuid,
+        retention: Duration,
+    ) -> MetaResult<Vec<TopicSplit>> {
+        let make_error = || MetaError("failed to fetch expired splits".to_string());
+        let pool = self.pool.clone();
+
+        sqlx::query_as("SELECT topic_id, topic_name, start_offset, end_offset, split_id FROM topic_splits WHERE topic_id = $1 AND created_at < clock_timestamp() - make_interval(secs => $2) ORDER BY end_offset ASC")
+            .bind(topic_id)
+            .bind(retention.as_secs_f64())
+            .fetch_all(&pool)
+            .await
+            .change_context_lazy(make_error)
+    }
+
+    /// Deletes the metadata of the given splits of the topic; the splits are no longer visible to
+    /// readers afterwards.
+    ///
+    /// The start offset of the topic is advanced to the end offset of the last split deleted, so
+    /// that reads below it fail as out of range rather than skip to the splits that remain. Returns
+    /// the start offset afterwards, which never moves backwards.
+    #[fastrace::trace]
+    pub async fn delete_splits(
+        &self,
+        topic_id: uuid::Uuid,
+        split_ids: &[String],
+    ) -> MetaResult<i64> {
+        let make_error = || MetaError("failed to delete splits".to_string());
+        let pool = self.pool.clone();
+
+        let mut txn = pool.begin().await.change_context_lazy(make_error)?;
+
+        // lock the topic offsets so that the start offset is not moved by concurrent trims
+        let start_offset: i64 = sqlx::query_scalar(
+            "SELECT start_offset FROM topic_offsets WHERE topic_id = $1 FOR UPDATE",
+        )
+        .bind(topic_id)
+        .fetch_one(&mut *txn)
+        .await
+        .change_context_lazy(make_error)?;
+
+        let end_offsets: Vec<i64> = sqlx::query_scalar(
+            "DELETE FROM topic_splits WHERE topic_id = $1 AND split_id = ANY($2) RETURNING end_offset",
+        )
+        .bind(topic_id)
+        .bind(split_ids)
+        .fetch_all(&mut *txn)
+        .await
+        .change_context_lazy(make_error)?;
+        let start_offset = end_offsets.into_iter().fold(start_offset, i64::max);
+
+        sqlx::query("UPDATE topic_offsets SET start_offset = $1 WHERE topic_id = $2")
+            .bind(start_offset)
+            .bind(topic_id)
+            .execute(&mut *txn)
+            .await
+            .change_context_lazy(make_error)?;
+
+        txn.commit().await.change_context_lazy(make_error)?;
+        Ok(start_offset)
+    }
+
+    /// Advances the start offset of the topic to `offset`, and deletes the metadata of the splits
+    /// that end at or below it.
+    ///
+    /// Returns the start offset after trimming, which never moves backwards, and the deleted
+    /// splits so that the caller can clean up the split objects.
+    #[fastrace::trace]
+    pub async fn trim_topic(
+        &self,
+        topic_name: String,
+        offset: i64,
+    ) -> MetaResult<(i64, Vec<TopicSplit>)> {
+        let make_error = || MetaError(format!("failed to trim topic {topic_name}"));
+        let pool = self.pool.clone();
+
+        let topic = self.get_topics_by_name(topic_name.clone()).await?;
+
+        let mut txn = pool.begin().await.change_context_lazy(make_error)?;
+
+        // lock the topic offsets so that the end offset checked is not moved by concurrent commits
+        let (start_offset, last_offset): (i64, i64) = sqlx::query_as(
+            "SELECT start_offset, last_offset FROM topic_offsets WHERE topic_id = $1 FOR UPDATE",
+        )
+        .bind(topic.id)
+        .fetch_one(&mut *txn)
+        .await
+        .change_context_lazy(make_error)?;
+        if offset > last_offset {
+            return Err(Report::new(make_error())
+                .attach_printable(format!(
+                    "trim offset {offset} is beyond the end offset {last_offset}"
+                ))
+                .attach(ErrorCode::OutOfRange));
+        }
+        let start_offset = start_offset.max(offset);
+
+        sqlx::query("UPDATE topic_offsets SET start_offset = $1 WHERE topic_id = $2")
+            .bind(start_offset)
+            .bind(topic.id)
+            .execute(&mut *txn)
+            .await
+            .change_context_lazy(make_error)?;
+
+        let splits = sqlx::query_as("DELETE FROM topic_splits WHERE topic_id = $1 AND end_offset <= $2 RETURNING topic_id, topic_name, start_offset, end_offset, split_id")
+            .bind(topic.id)
+            .bind(start_offset)
+            .fetch_all(&mut *txn)
+            .await
+            .change_context_lazy(make_error)?;
+
+        txn.commit().await.change_context_lazy(make_error)?;
+        Ok((start_offset, splits))
+    }
+
+    /// Returns the start offset and the end offset of the topic, i.e., the first offset readable
+    /// and the offset the next entry will be appended at.
+    #[fastrace::trace]
+    pub async fn get_topic_offsets(&self, topic_id: uuid::Uuid) -> MetaResult<(i64, i64)> {
+        let make_error = || MetaError("failed to get topic offsets".to_string());
+        let pool = self.pool.clone();
+
+        sqlx::query_as("SELECT start_offset, last_offset FROM topic_offsets WHERE topic_id = $1")
+            .bind(topic_id)
+            .fetch_one(&pool)
+            .await
+            .change_context_lazy(make_error)
+    }
+
+    /// Seals the topic to appends, or unseals it if `sealed` is false; reads are not affected.
+    #[fastrace::trace]
+    pub async fn seal_topic(&self, topic_name: String, sealed: bool) -> MetaResult<()> {
+        let make_error = || MetaError(format!("failed to seal topic {topic_name}"));
+        let pool = self.pool.clone();
+
+        let result = sqlx::query("UPDATE topic_offsets SET sealed = $1 FROM topics WHERE topics.id = topic_offsets.topic_id AND topics.name = $2")
+            .bind(sealed)
+            .bind(&topic_name)
+            .execute(&pool)
+            .await
+            .change_context_lazy(make_error)?;
+        if result.rows_affected() == 0 {
+            return Err(
+                Report::new(MetaError(format!("topic not found: {topic_name}")))
+                    .attach(ErrorCode::NotFound),
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the split of the given id, which is unique across all topics.
+    #[fastrace::trace]
+    pub async fn get_split(&self, split_id: String) -> MetaResult<TopicSplit> {
+        let make_error = || MetaError("failed to get split".to_string());
+        let pool = self.pool.clone();
+
+        let split = sqlx::query_as("SELECT topic_id, topic_name, start_offset, end_offset, split_id FROM topic_splits WHERE split_id = $1")
+            .bind(&split_id)
+            .fetch_optional(&pool)
+            .await
+            .change_context_lazy(make_error)?;
+        split.ok_or_else(|| {
+            Report::new(MetaError(format!("split not found: {split_id}")))
+                .attach(ErrorCode::NotFound)
+        })
+    }
 }
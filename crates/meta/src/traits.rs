@@ -0,0 +1,228 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use error_stack::Report;
+
+use crate::CommitRecordBatchesRequest;
+use crate::CreateTopicRequest;
+use crate::FetchRecordBatchesRequest;
+use crate::MetaError;
+use crate::PostgresMetaService;
+use crate::Topic;
+use crate::TopicSplit;
+
+/// The meta operations the brokers rely on, so that they are not bound to a specific backend.
+///
+/// Every operation fails by default, so that a meta service for testing only implements the
+/// operations it serves.
+#[async_trait::async_trait]
+pub trait MetaService: std::fmt::Debug + Send + Sync {
+    /// Checks that the meta backend answers queries.
+    async fn ping(&self) -> error_stack::Result<(), MetaError> {
+        Err(unsupported("ping"))
+    }
+
+    async fn create_topic(
+        &self,
+        _request: CreateTopicRequest,
+    ) -> error_stack::Result<Topic, MetaError> {
+        Err(unsupported("create_topic"))
+    }
+
+    /// Deletes the topic along with its offsets and splits metadata; returns `None` if the topic
+    /// does not exist.
+    async fn delete_topic(
+        &self,
+        _topic_name: String,
+    ) -> error_stack::Result<Option<(Topic, Vec<TopicSplit>)>, MetaError> {
+        Err(unsupported("delete_topic"))
+    }
+
+    async fn get_topics_by_name(
+        &self,
+        _topic_name: String,
+    ) -> error_stack::Result<Topic, MetaError> {
+        Err(unsupported("get_topics_by_name"))
+    }
+
+    async fn fetch_record_batches(
+        &self,
+        _request: FetchRecordBatchesRequest,
+    ) -> error_stack::Result<Vec<TopicSplit>, MetaError> {
+        Err(unsupported("fetch_record_batches"))
+    }
+
+    /// Commits the split of `request.split_id` and returns the offsets range assigned to it;
+    /// committing the same split twice must return the same offsets range.
+    ///
+    /// A commit that may have been applied despite the error, e.g., the connection broke after the
+    /// commit was sent, must fail with [`crate::CommitIndeterminate`] so that the split is kept.
+    async fn commit_record_batches(
+        &self,
+        _request: CommitRecordBatchesRequest,
+    ) -> error_stack::Result<(i64, i64), MetaError> {
+        Err(unsupported("commit_record_batches"))
+    }
+
+    async fn get_all_topics(&self) -> error_stack::Result<Vec<Topic>, MetaError> {
+        Err(unsupported("get_all_topics"))
+    }
+
+    /// Returns the splits of the topic committed more than `retention` ago, in offsets order.
+    async fn fetch_expired_splits(
+        &self,
+        _topic_id: uuid::Uuid,
+        _retention: Duration,
+    ) -> error_stack::Result<Vec<TopicSplit>, MetaError> {
+        Err(unsupported("fetch_expired_splits"))
+    }
+
+    /// Deletes the metadata of the given splits of the topic, and advances the start offset of the
+    /// topic past them; returns the start offset afterwards.
+    async fn delete_splits(
+        &self,
+        _topic_id: uuid::Uuid,
+        _split_ids: &[String],
+    ) -> error_stack::Result<i64, MetaError> {
+        Err(unsupported("delete_splits"))
+    }
+
+    async fn get_split(&self, _split_id: String) -> error_stack::Result<TopicSplit, MetaError> {
+        Err(unsupported("get_split"))
+    }
+
+    /// Advances the start offset of the topic to `offset` and deletes the metadata of the splits
+    /// below it; returns the start offset after trimming and the deleted splits.
+    async fn trim_topic(
+        &self,
+        _topic_name: String,
+        _offset: i64,
+    ) -> error_stack::Result<(i64, Vec<TopicSplit>), MetaError> {
+        Err(unsupported("trim_topic"))
+    }
+
+    /// Returns the start offset and the end offset of the topic.
+    async fn get_topic_offsets(
+        &self,
+        _topic_id: uuid::Uuid,
+    ) -> error_stack::Result<(i64, i64), MetaError> {
+        Err(unsupported("get_topic_offsets"))
+    }
+
+    /// Seals the topic to appends, or unseals it if `sealed` is false.
+    async fn seal_topic(
+        &self,
+        _topic_name: String,
+        _sealed: bool,
+    ) -> error_stack::Result<(), MetaError> {
+        Err(unsupported("seal_topic"))
+    }
+}
+
+fn unsupported(operation: &str) -> Report<MetaError> {
+    Report::new(MetaError(format!(
+        "{operation} is not supported by this meta service"
+    )))
+}
+
+#[async_trait::async_trait]
+impl MetaService for PostgresMetaService {
+    async fn ping(&self) -> error_stack::Result<(), MetaError> {
+        PostgresMetaService::ping(self).await
+    }
+
+    async fn create_topic(
+        &self,
+        request: CreateTopicRequest,
+    ) -> error_stack::Result<Topic, MetaError> {
+        PostgresMetaService::create_topic(self, request).await
+    }
+
+    async fn delete_topic(
+        &self,
+        topic_name: String,
+    ) -> error_stack::Result<Option<(Topic, Vec<TopicSplit>)>, MetaError> {
+        PostgresMetaService::delete_topic(self, topic_name).await
+    }
+
+    async fn get_topics_by_name(
+        &self,
+        topic_name: String,
+    ) -> error_stack::Result<Topic, MetaError> {
+        PostgresMetaService::get_topics_by_name(self, topic_name).await
+    }
+
+    async fn fetch_record_batches(
+        &self,
+        request: FetchRecordBatchesRequest,
+    ) -> error_stack::Result<Vec<TopicSplit>, MetaError> {
+        PostgresMetaService::fetch_record_batches(self, request).await
+    }
+
+    async fn commit_record_batches(
+        &self,
+        request: CommitRecordBatchesRequest,
+    ) -> error_stack::Result<(i64, i64), MetaError> {
+        PostgresMetaService::commit_record_batches(self, request).await
+    }
+
+    async fn get_all_topics(&self) -> error_stack::Result<Vec<Topic>, MetaError> {
+        PostgresMetaService::get_all_topics(self).await
+    }
+
+    async fn fetch_expired_splits(
+        &self,
+        topic_id: uuid::Uuid,
+        retention: Duration,
+    ) -> error_stack::Result<Vec<TopicSplit>, MetaError> {
+        PostgresMetaService::fetch_expired_splits(self, topic_id, retention).await
+    }
+
+    async fn delete_splits(
+        &self,
+        topic_id: uuid::Uuid,
+        split_ids: &[String],
+    ) -> error_stack::Result<i64, MetaError> {
+        PostgresMetaService::delete_splits(self, topic_id, split_ids).await
+    }
+
+    async fn get_split(&self, split_id: String) -> error_stack::Result<TopicSplit, MetaError> {
+        PostgresMetaService::get_split(self, split_id).await
+    }
+
+    async fn trim_topic(
+        &self,
+        topic_name: String,
+        offset: i64,
+    ) -> error_stack::Result<(i64, Vec<TopicSplit>), MetaError> {
+        PostgresMetaService::trim_topic(self, topic_name, offset).await
+    }
+
+    async fn get_topic_offsets(
+        &self,
+        topic_id: uuid::Uuid,
+    ) -> error_stack::Result<(i64, i64), MetaError> {
+        PostgresMetaService::get_topic_offsets(self, topic_id).await
+    }
+
+    async fn seal_topic(
+        &self,
+        topic_name: String,
+        sealed: bool,
+    ) -> error_stack::Result<(), MetaError> {
+        PostgresMetaService::seal_topic(self, topic_name, sealed).await
+    }
+}
@@ -49,6 +49,7 @@ struct GlobalRuntimes {
     server_runtime: Runtime,
     exec_runtime: Runtime,
     io_runtime: Runtime,
+    storage_runtime: Runtime,
 }
 
 static GLOBAL_RUNTIMES: OnceLock<GlobalRuntimes> = OnceLock::new();
@@ -66,6 +67,7 @@ fn do_initialize_runtimes(opts: &RuntimeOptions) -> GlobalRuntimes {
         server_runtime_threads,
         exec_runtime_threads,
         io_runtime_threads,
+        storage_runtime_threads,
     } = opts;
 
     let server_runtime = make_runtime(
@@ -87,11 +89,19 @@ fn do_initialize_runtimes(opts: &RuntimeOptions) -> GlobalRuntimes {
         "io_thread",
         io_runtime_threads.unwrap_or_else(default_io_threads).get(),
     );
+    let storage_runtime = make_runtime(
+        "storage_runtime",
+        "storage_thread",
+        storage_runtime_threads
+            .unwrap_or_else(default_storage_threads)
+            .get(),
+    );
 
     GlobalRuntimes {
         server_runtime,
         exec_runtime,
         io_runtime,
+        storage_runtime,
     }
 }
 
@@ -107,6 +117,10 @@ fn default_io_threads() -> NonZeroUsize {
     num_cpus()
 }
 
+fn default_storage_threads() -> NonZeroUsize {
+    num_cpus()
+}
+
 fn set_panic_hook() {
     std::panic::set_hook(Box::new(move |info| {
         let backtrace = std::backtrace::Backtrace::force_capture();
@@ -133,6 +147,12 @@ pub fn io_runtime() -> &'static Runtime {
     &fetch_runtimes_or_default().io_runtime
 }
 
+/// The runtime where the storage operations run, so that slow object storage requests do not hold
+/// up the threads serving the API.
+pub fn storage_runtime() -> &'static Runtime {
+    &fetch_runtimes_or_default().storage_runtime
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,11 +167,19 @@ mod tests {
 
         let handle = io_runtime().spawn(async { 4 + 4 });
         assert_eq!(8, io_runtime().block_on(handle));
+
+        let handle = storage_runtime().spawn(async { 8 + 8 });
+        assert_eq!(16, storage_runtime().block_on(handle));
     }
 
     #[test]
     fn test_spawn_from_blocking() {
-        let runtimes = [server_runtime(), exec_runtime(), io_runtime()];
+        let runtimes = [
+            server_runtime(),
+            exec_runtime(),
+            io_runtime(),
+            storage_runtime(),
+        ];
 
         for runtime in runtimes {
             let out = runtime.block_on(async move {
@@ -66,6 +66,46 @@ impl Runtime {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Returns the metrics of the underlying tokio runtime.
+    pub fn metrics(&self) -> tokio::runtime::RuntimeMetrics {
+        self.runtime.metrics()
+    }
+
+    /// Takes a snapshot of the load of this runtime.
+    pub fn stats(&self) -> RuntimeStats {
+        let metrics = self.metrics();
+        RuntimeStats {
+            workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            global_queue_depth: metrics.global_queue_depth(),
+            #[cfg(feature = "unstable-metrics")]
+            blocking_threads: Some(metrics.num_blocking_threads()),
+            #[cfg(not(feature = "unstable-metrics"))]
+            blocking_threads: None,
+            #[cfg(feature = "unstable-metrics")]
+            blocking_queue_depth: Some(metrics.blocking_queue_depth()),
+            #[cfg(not(feature = "unstable-metrics"))]
+            blocking_queue_depth: None,
+        }
+    }
+}
+
+/// A snapshot of the load of a runtime, for telling task queueing, blocking pool exhaustion and
+/// worker starvation apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeStats {
+    /// Number of worker threads.
+    pub workers: usize,
+    /// Number of tasks spawned and not yet finished.
+    pub alive_tasks: usize,
+    /// Number of tasks waiting in the global queue for a worker.
+    pub global_queue_depth: usize,
+    /// Number of threads in the blocking pool; requires the `unstable-metrics` feature.
+    pub blocking_threads: Option<usize>,
+    /// Number of tasks waiting for a thread in the blocking pool; requires the
+    /// `unstable-metrics` feature.
+    pub blocking_queue_depth: Option<usize>,
 }
 
 impl fastimer::Spawn for &'static Runtime {
@@ -225,6 +265,20 @@ mod tests {
         assert_eq!(out, "hello")
     }
 
+    #[test]
+    fn test_stats() {
+        let rt = runtime();
+        let (tx, rx) = oneshot::channel::<()>();
+        let handle = rt.spawn(async move { rx.await.unwrap() });
+
+        let stats = rt.stats();
+        assert_eq!(stats.workers, 2);
+        assert_eq!(stats.alive_tasks, 1);
+
+        tx.send(()).unwrap();
+        rt.block_on(handle);
+    }
+
     #[test]
     fn test_spawn_join() {
         let rt = runtime();
@@ -20,11 +20,18 @@ use error_stack::Result;
 use error_stack::ResultExt;
 use mea::latch::Latch;
 use mea::waitgroup::WaitGroup;
-use morax_meta::PostgresMetaService;
+use morax_broker::DrainReport;
+use morax_broker::DrainTracker;
+use morax_meta::MetaService;
 use morax_protos::config::BrokerConfig;
+use morax_protos::config::TlsConfig;
+use morax_storage::StorageContext;
 use poem::listener::Acceptor;
 use poem::listener::Listener;
+use poem::listener::RustlsCertificate;
+use poem::listener::RustlsConfig;
 
+use crate::metrics::render_metrics;
 use crate::server::resolve_advertise_addr;
 use crate::server::ServerFuture;
 use crate::ServerError;
@@ -32,23 +39,30 @@ use crate::ServerError;
 #[derive(Debug)]
 pub(crate) struct BrokerBootstrapContext {
     pub(crate) config: BrokerConfig,
-    pub(crate) meta_service: Arc<PostgresMetaService>,
+    pub(crate) meta_service: Arc<dyn MetaService>,
+    pub(crate) storage: StorageContext,
     pub(crate) wg: WaitGroup,
     pub(crate) shutdown: Arc<Latch>,
 }
 
 pub(crate) async fn bootstrap_broker(
     context: BrokerBootstrapContext,
-) -> Result<(SocketAddr, ServerFuture<()>), ServerError> {
+) -> Result<(SocketAddr, ServerFuture<DrainReport>), ServerError> {
     let BrokerBootstrapContext {
         config,
         meta_service,
+        storage,
         wg,
         shutdown,
     } = context;
 
     let broker_addr = config.listen_addr.as_str();
-    let broker_acceptor = poem::listener::TcpListener::bind(broker_addr)
+    let broker_listener = poem::listener::TcpListener::bind(broker_addr);
+    let broker_listener = match config.tls {
+        Some(ref tls) => broker_listener.rustls(make_rustls_config(tls)?).boxed(),
+        None => broker_listener.boxed(),
+    };
+    let broker_acceptor = broker_listener
         .into_acceptor()
         .await
         .change_context_lazy(|| {
@@ -65,22 +79,46 @@ pub(crate) async fn bootstrap_broker(
         let shutdown_clone = shutdown;
         let wg_clone = wg;
 
-        let route = morax_broker::make_api_router(meta_service);
+        let drain = Arc::new(DrainTracker::default());
+        let drain_clone = drain.clone();
+
+        let route = morax_broker::make_api_router(&config, meta_service, storage, drain.clone())
+            .at("/metrics", poem::get(render_metrics));
         let signal = async move {
             log::info!("Broker has started on [{broker_listen_addr}]");
             drop(wg_clone);
 
             shutdown_clone.wait().await;
             log::info!("Broker is closing");
+            drain_clone.start_drain();
         };
 
         morax_runtime::server_runtime().spawn(async move {
             poem::Server::new_with_acceptor(broker_acceptor)
                 .run_with_graceful_shutdown(route, signal, Some(Duration::from_secs(30)))
                 .await
-                .change_context_lazy(|| ServerError("failed to run the broker".to_string()))
+                .change_context_lazy(|| ServerError("failed to run the broker".to_string()))?;
+            Ok(drain.report())
         })
     };
 
     Ok((broker_advertise_addr, broker_fut))
 }
+
+fn make_rustls_config(config: &TlsConfig) -> Result<RustlsConfig, ServerError> {
+    let read_file = |path: &str| {
+        std::fs::read(path)
+            .change_context_lazy(|| ServerError(format!("failed to read TLS file: {path}")))
+    };
+
+    let certificate = RustlsCertificate::new()
+        .cert(read_file(&config.cert_path)?)
+        .key(read_file(&config.key_path)?);
+    let rustls_config = RustlsConfig::new().fallback(certificate);
+    match config.client_ca_path {
+        Some(ref client_ca_path) => {
+            Ok(rustls_config.client_auth_required(read_file(client_ca_path)?))
+        }
+        None => Ok(rustls_config),
+    }
+}
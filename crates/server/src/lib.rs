@@ -18,3 +18,5 @@ mod server;
 pub use server::*;
 
 mod broker;
+mod metrics;
+mod retention;
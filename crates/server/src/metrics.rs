@@ -0,0 +1,70 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::OnceLock;
+
+use metrics_exporter_prometheus::PrometheusBuilder;
+use metrics_exporter_prometheus::PrometheusHandle;
+
+/// Returns the handle of the process-wide Prometheus recorder, installing it on first use.
+fn prometheus_handle() -> &'static PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+    HANDLE.get_or_init(|| {
+        PrometheusBuilder::new()
+            .install_recorder()
+            .expect("failed to install Prometheus recorder")
+    })
+}
+
+/// Initializes the metrics recorder so that metrics recorded before the first scrape are kept.
+pub(crate) fn init_metrics() {
+    prometheus_handle();
+}
+
+#[poem::handler]
+pub(crate) fn render_metrics() -> String {
+    let handle = prometheus_handle();
+    // drain histogram samples into their summaries; each scrape drives the upkeep
+    handle.run_upkeep();
+    record_runtime_metrics();
+    handle.render()
+}
+
+/// Samples the load of the global runtimes; they are recorded on scrape as they are point in time.
+fn record_runtime_metrics() {
+    let runtimes = [
+        morax_runtime::server_runtime(),
+        morax_runtime::exec_runtime(),
+        morax_runtime::io_runtime(),
+        morax_runtime::storage_runtime(),
+    ];
+    for runtime in runtimes {
+        let stats = runtime.stats();
+        let name = runtime.name().to_string();
+        metrics::gauge!("morax_runtime_workers", "runtime" => name.clone())
+            .set(stats.workers as f64);
+        metrics::gauge!("morax_runtime_alive_tasks", "runtime" => name.clone())
+            .set(stats.alive_tasks as f64);
+        metrics::gauge!("morax_runtime_global_queue_depth", "runtime" => name.clone())
+            .set(stats.global_queue_depth as f64);
+        if let Some(blocking_threads) = stats.blocking_threads {
+            metrics::gauge!("morax_runtime_blocking_threads", "runtime" => name.clone())
+                .set(blocking_threads as f64);
+        }
+        if let Some(blocking_queue_depth) = stats.blocking_queue_depth {
+            metrics::gauge!("morax_runtime_blocking_queue_depth", "runtime" => name)
+                .set(blocking_queue_depth as f64);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use error_stack::Result;
+use error_stack::ResultExt;
+use mea::latch::Latch;
+use morax_meta::MetaService;
+use morax_meta::Topic;
+use morax_storage::StorageContext;
+use morax_storage::TopicStorage;
+
+use crate::ServerError;
+
+/// Periodically deletes the splits past the retention of their topics until the server shuts
+/// down.
+///
+/// The metadata of the expired splits is deleted before their objects, which advances the start
+/// offset of the topic past them, the same as trimming. Readers then no longer resolve the expired
+/// splits, and a read below the start offset fails as out of range. If deleting the objects fails
+/// afterwards, they are left orphaned, the same as the objects of a trimmed or deleted log.
+pub(crate) async fn run_retention(
+    meta: Arc<dyn MetaService>,
+    storage: StorageContext,
+    interval: Duration,
+    shutdown: Arc<Latch>,
+) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = shutdown.wait() => return,
+        }
+
+        let topics = match meta.get_all_topics().await {
+            Ok(topics) => topics,
+            Err(err) => {
+                log::warn!(err:?; "failed to list logs for retention");
+                continue;
+            }
+        };
+        for topic in topics {
+            let topic_name = topic.name.clone();
+            if let Err(err) = expire_splits(meta.as_ref(), &storage, topic).await {
+                log::warn!(err:?; "failed to delete expired splits of log {topic_name}");
+            }
+        }
+    }
+}
+
+async fn expire_splits(
+    meta: &dyn MetaService,
+    storage: &StorageContext,
+    topic: Topic,
+) -> Result<(), ServerError> {
+    let make_error = || ServerError(format!("failed to expire splits of log {}", topic.name));
+
+    let Some(retention_ms) = topic.properties.0.retention_ms else {
+        return Ok(());
+    };
+    let retention = Duration::from_millis(retention_ms.get());
+
+    let split_ids = meta
+        .fetch_expired_splits(topic.id, retention)
+        .await
+        .change_context_lazy(make_error)?
+        .into_iter()
+        .map(|split| split.split_id)
+        .collect::<Vec<_>>();
+    if split_ids.is_empty() {
+        return Ok(());
+    }
+
+    let start_offset = meta
+        .delete_splits(topic.id, &split_ids)
+        .await
+        .change_context_lazy(make_error)?;
+    log::info!(
+        "deleted {} expired splits of log {}; the start offset is {start_offset}",
+        split_ids.len(),
+        topic.name
+    );
+
+    let topic_storage = TopicStorage::new(topic.properties.0.storage.clone(), storage.clone());
+    if let Err(err) = topic_storage.delete_splits(&topic.name, &split_ids).await {
+        log::warn!(err:?; "failed to delete expired split objects of log {}", topic.name);
+    }
+    Ok(())
+}
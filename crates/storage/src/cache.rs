@@ -0,0 +1,173 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+type SplitKey = (String, String);
+
+/// A least-recently-used cache of split contents, bounded by the total bytes of cached splits.
+#[derive(Debug)]
+pub struct SplitCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    size: usize,
+    tick: u64,
+    entries: HashMap<SplitKey, CacheEntry>,
+    // access tick -> key; the first entry is the least recently used one
+    lru: BTreeMap<u64, SplitKey>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    tick: u64,
+    data: Vec<u8>,
+}
+
+impl SplitCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, topic_name: &str, split_id: &str) -> Option<Vec<u8>> {
+        let key = (topic_name.to_string(), split_id.to_string());
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        let Some(entry) = state.entries.get_mut(&key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        state.tick += 1;
+        let key = state
+            .lru
+            .remove(&entry.tick)
+            .expect("lru must track all entries");
+        entry.tick = state.tick;
+        state.lru.insert(entry.tick, key);
+
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry.data.clone())
+    }
+
+    pub fn insert(&self, topic_name: &str, split_id: &str, data: Vec<u8>) {
+        if data.len() > self.capacity {
+            return;
+        }
+
+        let key = (topic_name.to_string(), split_id.to_string());
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        state.tick += 1;
+        state.size += data.len();
+        state.lru.insert(state.tick, key.clone());
+        let entry = CacheEntry {
+            tick: state.tick,
+            data,
+        };
+        if let Some(replaced) = state.entries.insert(key, entry) {
+            state.lru.remove(&replaced.tick);
+            state.size -= replaced.data.len();
+        }
+
+        while state.size > self.capacity {
+            let (_, key) = state.lru.pop_first().expect("cache size must be tracked");
+            let evicted = state
+                .entries
+                .remove(&key)
+                .expect("lru must track all entries");
+            state.size -= evicted.data.len();
+        }
+    }
+
+    pub fn remove(&self, topic_name: &str, split_id: &str) {
+        let key = (topic_name.to_string(), split_id.to_string());
+        let mut state = self.state.lock().unwrap();
+        if let Some(removed) = state.entries.remove(&key) {
+            state.lru.remove(&removed.tick);
+            state.size -= removed.data.len();
+        }
+    }
+
+    /// Returns the total bytes of the cached splits.
+    pub fn size(&self) -> usize {
+        self.state.lock().unwrap().size
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evict_by_size() {
+        let cache = SplitCache::new(10);
+        cache.insert("topic", "a", vec![0; 4]);
+        cache.insert("topic", "b", vec![0; 4]);
+        assert_eq!(cache.size(), 8);
+
+        // touch "a" so that "b" becomes the least recently used split
+        assert!(cache.get("topic", "a").is_some());
+        cache.insert("topic", "c", vec![0; 4]);
+        assert_eq!(cache.size(), 8);
+        assert!(cache.get("topic", "b").is_none());
+        assert!(cache.get("topic", "a").is_some());
+        assert!(cache.get("topic", "c").is_some());
+
+        // splits larger than the capacity are never cached
+        cache.insert("topic", "d", vec![0; 11]);
+        assert!(cache.get("topic", "d").is_none());
+        assert_eq!(cache.size(), 8);
+
+        assert_eq!(cache.hits(), 3);
+        assert_eq!(cache.misses(), 2);
+    }
+
+    #[test]
+    fn test_replace_and_remove() {
+        let cache = SplitCache::new(10);
+        cache.insert("topic", "a", vec![0; 4]);
+        cache.insert("topic", "a", vec![1; 6]);
+        assert_eq!(cache.size(), 6);
+        assert_eq!(cache.get("topic", "a"), Some(vec![1; 6]));
+
+        cache.remove("topic", "a");
+        assert_eq!(cache.size(), 0);
+        assert!(cache.get("topic", "a").is_none());
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Injects the faults configured for testing into the storage operations, so that tests can
+//! exercise how the failures are handled without an unreliable storage.
+
+use morax_protos::config::StorageFault;
+use morax_protos::config::StorageFaultKind;
+use morax_protos::config::StorageOperation;
+
+/// Fails the operation before it reaches the storage if a failing fault of it fires.
+pub(crate) fn inject_error(
+    faults: &[StorageFault],
+    operation: StorageOperation,
+) -> Result<(), opendal::Error> {
+    for fault in faults {
+        let err = match fault.kind {
+            StorageFaultKind::Unexpected => {
+                opendal::Error::new(opendal::ErrorKind::Unexpected, "injected storage fault")
+            }
+            StorageFaultKind::Temporary => {
+                opendal::Error::new(opendal::ErrorKind::Unexpected, "injected storage fault")
+                    .set_temporary()
+            }
+            StorageFaultKind::Corrupt => continue,
+        };
+        if fires(fault, operation) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Flips the bytes read from the storage if a corrupting fault fires.
+pub(crate) fn inject_corruption(faults: &[StorageFault], mut data: Vec<u8>) -> Vec<u8> {
+    for fault in faults {
+        if fault.kind == StorageFaultKind::Corrupt && fires(fault, StorageOperation::Read) {
+            data.iter_mut().for_each(|byte| *byte = !*byte);
+            break;
+        }
+    }
+    data
+}
+
+fn fires(fault: &StorageFault, operation: StorageOperation) -> bool {
+    if operation != fault.operation {
+        return false;
+    }
+    if fault.ratio >= 1.0 {
+        return true;
+    }
+    // UUIDs v4 are random enough for deciding a failure
+    let (random, _) = uuid::Uuid::new_v4().as_u64_pair();
+    (random as f64) < fault.ratio * u64::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_by_operation_and_ratio() {
+        let fault = StorageFault {
+            operation: StorageOperation::Write,
+            kind: StorageFaultKind::Temporary,
+            ratio: 1.0,
+        };
+        let faults = [fault.clone()];
+        let err = inject_error(&faults, StorageOperation::Write).unwrap_err();
+        assert!(err.is_temporary());
+        inject_error(&faults, StorageOperation::Read).unwrap();
+
+        let faults = [StorageFault {
+            ratio: 0.0,
+            ..fault
+        }];
+        inject_error(&faults, StorageOperation::Write).unwrap();
+    }
+
+    #[test]
+    fn test_inject_corruption() {
+        let fault = StorageFault {
+            operation: StorageOperation::Read,
+            kind: StorageFaultKind::Corrupt,
+            ratio: 1.0,
+        };
+        let faults = [fault.clone()];
+        inject_error(&faults, StorageOperation::Read).unwrap();
+        assert_eq!(
+            inject_corruption(&faults, vec![0x00, 0x0f]),
+            vec![0xff, 0xf0]
+        );
+
+        let faults = [StorageFault {
+            ratio: 0.0,
+            ..fault
+        }];
+        assert_eq!(
+            inject_corruption(&faults, vec![0x00, 0x0f]),
+            vec![0x00, 0x0f]
+        );
+    }
+}
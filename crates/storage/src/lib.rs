@@ -12,53 +12,481 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
 use error_stack::Result;
+use morax_protos::config::StorageConfig;
+#[cfg(any(test, feature = "fault-injection"))]
+use morax_protos::config::StorageFault;
+#[cfg(any(test, feature = "fault-injection"))]
+use morax_protos::config::StorageOperation;
 use morax_protos::property::StorageProps;
+use opendal::layers::RetryLayer;
+use opendal::ErrorKind;
 use opendal::Operator;
+use tokio::sync::Semaphore;
+
+mod cache;
+pub use cache::SplitCache;
+
+#[cfg(any(test, feature = "fault-injection"))]
+mod fault;
 
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
     #[error("{0}")]
     OpenDAL(opendal::Error),
+    #[error("split {0} read back does not match the records written")]
+    SplitMismatch(String),
+    #[error("split {0} is committed in the meta but missing in the storage")]
+    SplitMissing(String),
+}
+
+impl StorageError {
+    /// Tells a split missing in the storage apart from other failures to read it, since the
+    /// former means the storage and the meta are out of sync, e.g., deleted out of band.
+    fn from_read(split_id: &str, err: opendal::Error) -> Self {
+        if err.kind() == ErrorKind::NotFound {
+            metrics::counter!("morax_storage_missing_splits_total").increment(1);
+            StorageError::SplitMissing(split_id.to_string())
+        } else {
+            StorageError::OpenDAL(err)
+        }
+    }
+}
+
+/// States shared by the storages of all topics, such as the split cache.
+#[derive(Debug, Clone)]
+pub struct StorageContext {
+    cache: Option<Arc<SplitCache>>,
+    retry_max_times: usize,
+    retry_min_delay: Duration,
+    max_concurrent_ops: usize,
+    permits: Arc<Semaphore>,
+    #[cfg(any(test, feature = "fault-injection"))]
+    faults: Vec<StorageFault>,
+}
+
+impl Default for StorageContext {
+    fn default() -> Self {
+        Self::new(&StorageConfig::default())
+    }
+}
+
+impl StorageContext {
+    pub fn new(config: &StorageConfig) -> Self {
+        let cache = config
+            .split_cache_bytes
+            .map(|capacity| Arc::new(SplitCache::new(capacity.get())));
+        let retry_max_times = config.retry_max_times.unwrap_or(3);
+        let retry_min_delay = Duration::from_millis(config.retry_min_delay_ms.unwrap_or(100));
+        let max_concurrent_ops = config.max_concurrent_ops.map_or(16, NonZeroUsize::get);
+        Self {
+            cache,
+            retry_max_times,
+            retry_min_delay,
+            max_concurrent_ops,
+            permits: Arc::new(Semaphore::new(max_concurrent_ops)),
+            #[cfg(any(test, feature = "fault-injection"))]
+            faults: config.faults.clone(),
+        }
+    }
+
+    #[cfg(any(test, feature = "fault-injection"))]
+    fn inject_fault(&self, operation: StorageOperation) -> Result<(), StorageError> {
+        fault::inject_error(&self.faults, operation).map_err(StorageError::OpenDAL)?;
+        Ok(())
+    }
+
+    /// The maximum number of storage operations in flight at the same time.
+    pub fn max_concurrent_ops(&self) -> usize {
+        self.max_concurrent_ops
+    }
+
+    /// Runs a storage operation on the storage runtime, apart from the runtime serving the
+    /// request, once there is a slot for it.
+    ///
+    /// The slot is held until the operation finishes, even if the caller stops waiting for it.
+    async fn run<F, T>(&self, future: F) -> T
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("storage permits are never closed");
+        morax_runtime::storage_runtime()
+            .spawn(async move {
+                let _permit = permit;
+                future.await
+            })
+            .await
+    }
+
+    pub fn split_cache(&self) -> Option<&SplitCache> {
+        self.cache.as_deref()
+    }
 }
 
 pub struct TopicStorage {
     storage: StorageProps,
+    context: StorageContext,
 }
 
 impl TopicStorage {
-    pub fn new(storage: StorageProps) -> Self {
-        Self { storage }
+    pub fn new(storage: StorageProps, context: StorageContext) -> Self {
+        Self { storage, context }
     }
 
+    #[fastrace::trace]
     pub async fn read_at(&self, topic_name: &str, split_id: &str) -> Result<Vec<u8>, StorageError> {
+        if let Some(cache) = self.context.split_cache() {
+            if let Some(records) = cache.get(topic_name, split_id) {
+                return Ok(records);
+            }
+        }
+
+        #[cfg(any(test, feature = "fault-injection"))]
+        self.context.inject_fault(StorageOperation::Read)?;
         let op = self.op()?;
         let split_url = format!("{topic_name}/{split_id}");
-        let records = op.read(&split_url).await.map_err(StorageError::OpenDAL)?;
-        Ok(records.to_vec())
+        let start = Instant::now();
+        let records = self
+            .context
+            .run(async move { op.read(&split_url).await })
+            .await
+            .map_err(|err| StorageError::from_read(split_id, err))?;
+        metrics::histogram!("morax_storage_read_duration_seconds").record(start.elapsed());
+        let records = records.to_vec();
+        #[cfg(any(test, feature = "fault-injection"))]
+        let records = fault::inject_corruption(&self.context.faults, records);
+
+        if let Some(cache) = self.context.split_cache() {
+            cache.insert(topic_name, split_id, records.clone());
+        }
+        Ok(records)
     }
 
+    #[fastrace::trace]
     pub async fn write_to(
         &self,
         topic_name: &str,
         records: Vec<u8>,
     ) -> Result<String, StorageError> {
+        #[cfg(any(test, feature = "fault-injection"))]
+        self.context.inject_fault(StorageOperation::Write)?;
         let op = self.op()?;
         // TODO(tisonkun): whether use a sequential number rather than a UUID
         let split_id = uuid::Uuid::new_v4();
         let split_url = format!("{topic_name}/{split_id}");
-        op.write(&split_url, records)
+        let start = Instant::now();
+        self.context
+            .run(async move { op.write(&split_url, records).await })
             .await
             .map_err(StorageError::OpenDAL)?;
+        metrics::histogram!("morax_storage_write_duration_seconds").record(start.elapsed());
         Ok(split_id.to_string())
     }
 
-    fn op(&self) -> Result<Operator, StorageError> {
-        match self.storage.clone() {
-            StorageProps::S3(config) => {
-                let builder = Operator::from_config(config).map_err(StorageError::OpenDAL)?;
-                Ok(builder.finish())
+    /// Reads back the split from the underlying storage, bypassing the split cache, and checks that
+    /// it holds exactly the `records` written.
+    #[fastrace::trace]
+    pub async fn verify_split(
+        &self,
+        topic_name: &str,
+        split_id: &str,
+        records: &[u8],
+    ) -> Result<(), StorageError> {
+        #[cfg(any(test, feature = "fault-injection"))]
+        self.context.inject_fault(StorageOperation::Read)?;
+        let op = self.op()?;
+        let split_url = format!("{topic_name}/{split_id}");
+        let persisted = self
+            .context
+            .run(async move { op.read(&split_url).await })
+            .await
+            .map_err(|err| StorageError::from_read(split_id, err))?;
+        let persisted = persisted.to_vec();
+        #[cfg(any(test, feature = "fault-injection"))]
+        let persisted = fault::inject_corruption(&self.context.faults, persisted);
+        if persisted != records {
+            return Err(StorageError::SplitMismatch(split_id.to_string()).into());
+        }
+        Ok(())
+    }
+
+    #[fastrace::trace]
+    pub async fn delete_splits(
+        &self,
+        topic_name: &str,
+        split_ids: &[String],
+    ) -> Result<(), StorageError> {
+        #[cfg(any(test, feature = "fault-injection"))]
+        self.context.inject_fault(StorageOperation::Delete)?;
+        let op = self.op()?;
+        let mut split_urls = Vec::with_capacity(split_ids.len());
+        for split_id in split_ids {
+            if let Some(cache) = self.context.split_cache() {
+                cache.remove(topic_name, split_id);
             }
+            split_urls.push(format!("{topic_name}/{split_id}"));
         }
+        self.context
+            .run(async move {
+                for split_url in split_urls {
+                    op.delete(&split_url).await?;
+                }
+                Ok::<_, opendal::Error>(())
+            })
+            .await
+            .map_err(StorageError::OpenDAL)?;
+        Ok(())
+    }
+
+    fn op(&self) -> Result<Operator, StorageError> {
+        let op = match self.storage.clone() {
+            StorageProps::S3(config) => Operator::from_config(config)
+                .map_err(StorageError::OpenDAL)?
+                .finish(),
+            StorageProps::Fs(config) => Operator::from_config(config)
+                .map_err(StorageError::OpenDAL)?
+                .finish(),
+            StorageProps::Gcs(config) => Operator::from_config(config)
+                .map_err(StorageError::OpenDAL)?
+                .finish(),
+            StorageProps::Azblob(config) => Operator::from_config(config)
+                .map_err(StorageError::OpenDAL)?
+                .finish(),
+        };
+
+        // only temporary errors are retried, and each retry is logged at warn level
+        let retry = RetryLayer::new()
+            .with_max_times(self.context.retry_max_times)
+            .with_min_delay(self.context.retry_min_delay)
+            .with_jitter();
+        Ok(op.layer(retry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use morax_protos::config::StorageFaultKind;
+    use opendal::services::AzblobConfig;
+    use opendal::services::FsConfig;
+    use opendal::services::GcsConfig;
+    use opendal::Scheme;
+
+    use super::*;
+
+    #[test]
+    fn test_make_gcs_operator() {
+        let mut config = GcsConfig::default();
+        config.bucket = "test-bucket".to_string();
+        config.root = Some("/morax/".to_string());
+        config.disable_config_load = true;
+        config.disable_vm_metadata = true;
+
+        let op = TopicStorage::new(StorageProps::Gcs(config), StorageContext::default())
+            .op()
+            .unwrap();
+        assert_eq!(op.info().scheme(), Scheme::Gcs);
+        assert_eq!(op.info().name(), "test-bucket");
+    }
+
+    #[test]
+    fn test_make_azblob_operator() {
+        let mut config = AzblobConfig::default();
+        config.container = "test-container".to_string();
+        config.endpoint = Some("https://morax.blob.core.windows.net".to_string());
+        config.account_name = Some("morax".to_string());
+        config.root = Some("/morax/".to_string());
+
+        let op = TopicStorage::new(StorageProps::Azblob(config), StorageContext::default())
+            .op()
+            .unwrap();
+        assert_eq!(op.info().scheme(), Scheme::Azblob);
+        assert_eq!(op.info().name(), "test-container");
+    }
+
+    #[test]
+    fn test_read_from_split_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = FsConfig::default();
+        config.root = Some(dir.path().to_string_lossy().to_string());
+
+        let context = StorageContext::new(&StorageConfig {
+            split_cache_bytes: NonZeroUsize::new(1024),
+            ..StorageConfig::default()
+        });
+        let storage = TopicStorage::new(StorageProps::Fs(config), context.clone());
+
+        morax_runtime::test_runtime().block_on(async move {
+            let split_id = storage
+                .write_to("topic", b"records".to_vec())
+                .await
+                .unwrap();
+            let records = storage.read_at("topic", &split_id).await.unwrap();
+            assert_eq!(records, b"records");
+
+            // the second read must be served without touching the underlying storage
+            std::fs::remove_file(dir.path().join("topic").join(&split_id)).unwrap();
+            let records = storage.read_at("topic", &split_id).await.unwrap();
+            assert_eq!(records, b"records");
+        });
+
+        let cache = context.split_cache().unwrap();
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 1);
+    }
+
+    #[test]
+    fn test_inject_storage_faults() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = FsConfig::default();
+        config.root = Some(dir.path().to_string_lossy().to_string());
+        let make_storage = |faults| {
+            let context = StorageContext::new(&StorageConfig {
+                faults,
+                ..StorageConfig::default()
+            });
+            TopicStorage::new(StorageProps::Fs(config.clone()), context)
+        };
+
+        morax_runtime::test_runtime().block_on(async move {
+            // a failed write leaves nothing behind
+            let storage = make_storage(vec![StorageFault {
+                operation: StorageOperation::Write,
+                kind: StorageFaultKind::Unexpected,
+                ratio: 1.0,
+            }]);
+            let err = storage
+                .write_to("topic", b"records".to_vec())
+                .await
+                .unwrap_err();
+            assert!(matches!(err.current_context(), StorageError::OpenDAL(_)));
+            assert!(!dir.path().join("topic").exists());
+
+            let storage = make_storage(vec![StorageFault {
+                operation: StorageOperation::Read,
+                kind: StorageFaultKind::Corrupt,
+                ratio: 1.0,
+            }]);
+            let split_id = storage
+                .write_to("topic", b"records".to_vec())
+                .await
+                .unwrap();
+            let records = storage.read_at("topic", &split_id).await.unwrap();
+            assert_ne!(records, b"records");
+        });
+    }
+
+    #[test]
+    fn test_verify_split() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = FsConfig::default();
+        config.root = Some(dir.path().to_string_lossy().to_string());
+        let storage = TopicStorage::new(StorageProps::Fs(config), StorageContext::default());
+
+        morax_runtime::test_runtime().block_on(async move {
+            let split_id = storage
+                .write_to("topic", b"records".to_vec())
+                .await
+                .unwrap();
+            storage
+                .verify_split("topic", &split_id, b"records")
+                .await
+                .unwrap();
+
+            let split_path = dir.path().join("topic").join(&split_id);
+            std::fs::write(&split_path, b"corrupted").unwrap();
+            let err = storage
+                .verify_split("topic", &split_id, b"records")
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.current_context(),
+                StorageError::SplitMismatch(_)
+            ));
+
+            // a storage that silently drops the split
+            std::fs::remove_file(&split_path).unwrap();
+            let err = storage
+                .verify_split("topic", &split_id, b"records")
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.current_context(),
+                StorageError::SplitMissing(_)
+            ));
+            let err = storage.read_at("topic", &split_id).await.unwrap_err();
+            assert!(matches!(
+                err.current_context(),
+                StorageError::SplitMissing(_)
+            ));
+        });
+    }
+
+    #[test]
+    fn test_bounded_concurrent_ops() {
+        let context = StorageContext::new(&StorageConfig {
+            max_concurrent_ops: NonZeroUsize::new(1),
+            ..StorageConfig::default()
+        });
+
+        morax_runtime::test_runtime().block_on(async move {
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            let blocked = tokio::spawn({
+                let context = context.clone();
+                async move { context.run(async move { rx.await.unwrap() }).await }
+            });
+            while context.permits.available_permits() > 0 {
+                tokio::task::yield_now().await;
+            }
+
+            // the only slot is taken, so another operation must wait
+            let waiting = context.run(async {});
+            let timeout = tokio::time::timeout(Duration::from_millis(100), waiting).await;
+            assert!(timeout.is_err());
+
+            tx.send(()).unwrap();
+            blocked.await.unwrap();
+            context.run(async {}).await;
+        });
+    }
+
+    #[test]
+    fn test_hold_slot_until_op_finishes() {
+        let context = StorageContext::new(&StorageConfig {
+            max_concurrent_ops: NonZeroUsize::new(1),
+            ..StorageConfig::default()
+        });
+
+        morax_runtime::test_runtime().block_on(async move {
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            let abandoned = tokio::spawn({
+                let context = context.clone();
+                async move { context.run(async move { rx.await.unwrap() }).await }
+            });
+            while context.permits.available_permits() > 0 {
+                tokio::task::yield_now().await;
+            }
+
+            // the caller stops waiting, but the operation is still running on the storage runtime
+            abandoned.abort();
+            let _ = abandoned.await;
+            let waiting = context.run(async {});
+            let timeout = tokio::time::timeout(Duration::from_millis(100), waiting).await;
+            assert!(timeout.is_err());
+
+            tx.send(()).unwrap();
+            context.run(async {}).await;
+        });
     }
 }
@@ -12,23 +12,171 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::any::Any;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use fastrace::collector::SpanContext;
+use fastrace::collector::TraceId;
+use fastrace::Span;
+use fastrace_opentelemetry::OpenTelemetryReporter;
 use logforth::append;
+use logforth::append::rolling_file::NonBlockingBuilder;
+use logforth::append::rolling_file::RollingFile;
+use logforth::append::rolling_file::RollingFileWriter;
+use logforth::append::rolling_file::Rotation;
+use logforth::diagnostic::FastraceDiagnostic;
 use logforth::filter::env_filter::EnvFilterBuilder;
 use logforth::filter::EnvFilter;
+use logforth::layout::JsonLayout;
+use morax_protos::config::FileAppenderConfig;
+use morax_protos::config::LogFormat;
+use morax_protos::config::LogRotation;
 use morax_protos::config::TelemetryConfig;
+use morax_protos::config::TracesConfig;
+use opentelemetry::trace::SpanKind;
+use opentelemetry::InstrumentationScope;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+
+/// A guard that must be held until the process exits, so that buffered telemetry data is flushed.
+pub type DropGuard = Box<dyn Any + Send>;
 
-pub fn init(config: &TelemetryConfig) {
+pub fn init(config: &TelemetryConfig) -> Vec<DropGuard> {
+    let mut drop_guards = Vec::<DropGuard>::new();
     let mut logger = logforth::builder();
 
     // stderr logger
     if let Some(ref stderr) = config.log.stderr {
+        let append = match stderr.format {
+            LogFormat::Text => append::Stderr::default(),
+            LogFormat::Json => append::Stderr::default().with_layout(JsonLayout::default()),
+        };
         logger = logger.dispatch(|d| {
             d.filter(make_rust_log_filter_with_default_env(&stderr.filter))
-                .append(append::Stderr::default())
+                .diagnostic(FastraceDiagnostic::default())
+                .append(append)
+        });
+    }
+
+    // rolling file logger
+    if let Some(ref file) = config.log.file {
+        let (rolling_file, guard) = make_rolling_file(file);
+        drop_guards.push(Box::new(guard));
+        logger = logger.dispatch(|d| {
+            d.filter(make_rust_log_filter_with_default_env(&file.filter))
+                .diagnostic(FastraceDiagnostic::default())
+                .append(rolling_file)
         });
     }
 
     logger.apply();
+
+    // traces reporter
+    if let Some(ref traces) = config.traces {
+        drop_guards.push(Box::new(init_traces(traces)));
+    }
+
+    drop_guards
+}
+
+/// Flushes the spans pending to report on drop.
+struct TracesGuard;
+
+impl Drop for TracesGuard {
+    fn drop(&mut self) {
+        fastrace::flush();
+    }
+}
+
+fn init_traces(config: &TracesConfig) -> TracesGuard {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.otlp_endpoint)
+        .build()
+        .unwrap_or_else(|err| {
+            panic!(
+                "failed to create OTLP exporter to {}: {err}",
+                config.otlp_endpoint
+            )
+        });
+    let reporter = OpenTelemetryReporter::new(
+        exporter,
+        SpanKind::Server,
+        Cow::Owned(Resource::new([KeyValue::new("service.name", "morax")])),
+        InstrumentationScope::builder("morax").build(),
+    );
+    let sample_ratio = config.sample_ratio.unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&sample_ratio) {
+        panic!("trace sample ratio must be between 0.0 and 1.0: {sample_ratio}");
+    }
+    if SAMPLER.set(Sampler::new(sample_ratio)).is_err() {
+        log::warn!("traces sampler has been set; ignore sample ratio {sample_ratio}");
+    }
+    fastrace::set_reporter(reporter, fastrace::collector::Config::default());
+    TracesGuard
+}
+
+static SAMPLER: OnceLock<Sampler> = OnceLock::new();
+
+/// Starts the root span of a request, or a noop span if its trace is not sampled, so that no spans
+/// are recorded for the requests whose traces are not exported.
+pub fn start_root_span(name: impl Into<Cow<'static, str>>, span_context: SpanContext) -> Span {
+    match SAMPLER.get() {
+        Some(sampler) if !sampler.is_sampled(span_context.trace_id) => Span::noop(),
+        _ => Span::root(name, span_context),
+    }
+}
+
+/// Samples traces by their ids.
+///
+/// Trace ids are random, so a trace is sampled if its id falls into the lowest `sample_ratio` of
+/// the id space.
+struct Sampler {
+    threshold: u64,
+    sample_all: bool,
+}
+
+impl Sampler {
+    fn new(sample_ratio: f64) -> Self {
+        let threshold = (sample_ratio * u64::MAX as f64) as u64;
+        let sample_all = sample_ratio >= 1.0;
+        Self {
+            threshold,
+            sample_all,
+        }
+    }
+
+    fn is_sampled(&self, trace_id: TraceId) -> bool {
+        self.sample_all || (trace_id.0 as u64) < self.threshold
+    }
+}
+
+fn make_rolling_file(config: &FileAppenderConfig) -> (RollingFile, impl Any + Send) {
+    let rotation = match config.rotation {
+        LogRotation::Minutely => Rotation::Minutely,
+        LogRotation::Hourly => Rotation::Hourly,
+        LogRotation::Daily => Rotation::Daily,
+        LogRotation::Never => Rotation::Never,
+    };
+
+    let mut builder = RollingFileWriter::builder()
+        .rotation(rotation)
+        .filename_prefix(&config.name);
+    if let Some(max_files) = config.max_files {
+        builder = builder.max_log_files(max_files);
+    }
+    let writer = builder
+        .build(&config.dir)
+        .unwrap_or_else(|err| panic!("failed to create log files in {}: {err}", config.dir));
+
+    let (non_blocking, guard) = NonBlockingBuilder::default().finish(writer);
+    let rolling_file = match config.format {
+        LogFormat::Text => RollingFile::new(non_blocking),
+        LogFormat::Json => RollingFile::new(non_blocking).with_layout(JsonLayout::default()),
+    };
+    (rolling_file, guard)
 }
 
 fn make_rust_log_filter(filter: &str) -> EnvFilter {
@@ -45,3 +193,24 @@ fn make_rust_log_filter_with_default_env(filter: &str) -> EnvFilter {
         make_rust_log_filter(filter)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_traces_by_id() {
+        let sampler = Sampler::new(0.25);
+        let traces = 10000;
+        let sampled = (0..traces)
+            .filter(|_| sampler.is_sampled(TraceId::random()))
+            .count();
+        assert!(
+            (2000..3000).contains(&sampled),
+            "{sampled} of {traces} traces sampled"
+        );
+
+        assert!(Sampler::new(1.0).is_sampled(TraceId(u128::MAX)));
+        assert!(!Sampler::new(0.0).is_sampled(TraceId(0)));
+    }
+}
@@ -16,6 +16,7 @@ use std::future::Future;
 use std::process::ExitCode;
 
 use morax_protos::config::LogConfig;
+use morax_protos::config::LogFormat;
 use morax_protos::config::StderrAppenderConfig;
 use morax_protos::config::TelemetryConfig;
 use morax_protos::property::TopicProps;
@@ -31,12 +32,15 @@ where
     T: std::process::Termination,
     Fut: Send + Future<Output = T>,
 {
-    morax_telemetry::init(&TelemetryConfig {
+    let _telemetry_guards = morax_telemetry::init(&TelemetryConfig {
         log: LogConfig {
             stderr: Some(StderrAppenderConfig {
                 filter: "INFO".to_string(),
+                format: LogFormat::Text,
             }),
+            file: None,
         },
+        traces: None,
     });
 
     let test_name = make_test_name::<Fut>();
@@ -53,6 +57,9 @@ where
             client,
             topic_props: TopicProps {
                 storage: state.env_props.storage,
+                verify_writes: false,
+                max_split_bytes: None,
+                retention_ms: None,
             },
         })
         .await
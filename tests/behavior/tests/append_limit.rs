@@ -0,0 +1,117 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::property::TopicProps;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use test_harness::test;
+
+fn make_entries(n: usize) -> Vec<Entry> {
+    (0..n)
+        .map(|i| Entry {
+            index: None,
+            data: BASE64_STANDARD.encode(i.to_string()),
+        })
+        .collect()
+}
+
+#[test(harness)]
+async fn test_max_append_entries(testkit: Testkit) {
+    let name = "capped_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "capped_log" })"###);
+
+    // the test server uses the default cap of 10000 entries
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: name.clone(),
+            entries: make_entries(10001),
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::InvalidArgument));
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name,
+            entries: make_entries(10000),
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..10000 })");
+}
+
+#[test(harness)]
+async fn test_max_split_bytes(testkit: Testkit) {
+    let name = "small_split_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: TopicProps {
+                max_split_bytes: NonZeroUsize::new(1024),
+                ..testkit.topic_props.clone()
+            },
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "small_split_log" })"###);
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: name.clone(),
+            entries: make_entries(1000),
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::InvalidArgument));
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name,
+            entries: make_entries(10),
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..10 })");
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use morax_client::BatchingClient;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use test_harness::test;
+
+fn make_request(name: &str, payloads: &[&str]) -> AppendLogRequest {
+    AppendLogRequest {
+        name: name.to_string(),
+        entries: payloads
+            .iter()
+            .map(|payload| Entry {
+                index: None,
+                data: BASE64_STANDARD.encode(payload),
+            })
+            .collect(),
+        expected_offset: None,
+    }
+}
+
+#[test(harness)]
+async fn test_batching_client(testkit: Testkit) {
+    let name = "db_log";
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.to_string(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Success(_)));
+
+    let client = BatchingClient::new(testkit.client)
+        .with_linger(Duration::from_millis(100))
+        .with_max_batch_entries(100);
+
+    // the appends to the same log share a batch, and the batch of an absent log fails alone
+    let (a, b, c, d) = tokio::join!(
+        client.append_log(make_request(name, &["a0", "a1"])),
+        client.append_log(make_request(name, &["b0"])),
+        client.append_log(make_request(name, &["c0", "c1", "c2"])),
+        client.append_log(make_request("absent_log", &["d0"])),
+    );
+    let r = d.unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::NotFound));
+
+    let mut appended = vec![];
+    for (r, payloads) in [
+        (a, &["a0", "a1"][..]),
+        (b, &["b0"][..]),
+        (c, &["c0", "c1", "c2"][..]),
+    ] {
+        let HTTPResponse::Success(resp) = r.unwrap() else {
+            panic!("failed to append {payloads:?}");
+        };
+        assert_eq!(resp.offsets.end - resp.offsets.start, payloads.len() as i64);
+        appended.extend(resp.offsets.zip(payloads.iter().copied()));
+    }
+    appended.sort();
+
+    // each append gets back the offsets of its own entries
+    let r = client
+        .client()
+        .read_log(ReadLogRequest {
+            name: name.to_string(),
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    let HTTPResponse::Success(resp) = r else {
+        panic!("failed to read log");
+    };
+    let read = resp
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let data = BASE64_STANDARD.decode(entry.data).unwrap();
+            (entry.index.unwrap(), String::from_utf8(data).unwrap())
+        })
+        .collect::<Vec<_>>();
+    let appended = appended
+        .into_iter()
+        .map(|(index, payload)| (index, payload.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(read, appended);
+    assert_eq!(resp.next_offset, 6);
+}
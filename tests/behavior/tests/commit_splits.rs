@@ -0,0 +1,68 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use morax_meta::CommitRecordBatchesRequest;
+use morax_meta::CreateTopicRequest;
+use morax_meta::PostgresMetaService;
+use morax_protos::property::TopicProps;
+
+#[test]
+fn test_commit_record_batches_idempotent() {
+    let Some(state) = tests_toolkit::make_test_env_state("test_commit_record_batches_idempotent")
+    else {
+        return;
+    };
+
+    morax_runtime::test_runtime().block_on(async {
+        let meta = PostgresMetaService::new(&state.env_props.meta)
+            .await
+            .unwrap();
+        meta.create_topic(CreateTopicRequest {
+            name: "log".to_string(),
+            properties: TopicProps {
+                storage: state.env_props.storage.clone(),
+                verify_writes: false,
+                max_split_bytes: None,
+                retention_ms: None,
+            },
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+
+        let request = CommitRecordBatchesRequest {
+            topic_name: "log".to_string(),
+            record_len: 2,
+            split_id: "split-0".to_string(),
+            expected_offset: None,
+        };
+        let offsets = meta.commit_record_batches(request.clone()).await.unwrap();
+        assert_eq!(offsets, (0, 2));
+
+        // committing the same split again is a no-op
+        let offsets = meta.commit_record_batches(request).await.unwrap();
+        assert_eq!(offsets, (0, 2));
+
+        let offsets = meta
+            .commit_record_batches(CommitRecordBatchesRequest {
+                topic_name: "log".to_string(),
+                record_len: 3,
+                split_id: "split-1".to_string(),
+                expected_offset: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(offsets, (2, 5));
+    });
+}
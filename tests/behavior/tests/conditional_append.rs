@@ -0,0 +1,74 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use test_harness::test;
+
+fn make_request(name: &str, payload: &str, expected_offset: i64) -> AppendLogRequest {
+    AppendLogRequest {
+        name: name.to_string(),
+        entries: vec![Entry {
+            index: None,
+            data: BASE64_STANDARD.encode(payload),
+        }],
+        expected_offset: Some(expected_offset),
+    }
+}
+
+#[test(harness)]
+async fn test_conditional_append(testkit: Testkit) {
+    let name = "db_log";
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.to_string(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Success(_)));
+
+    // two appenders race for the same offset, and exactly one of them wins
+    let (a, b) = tokio::join!(
+        testkit.client.append_log(make_request(name, "a", 0)),
+        testkit.client.append_log(make_request(name, "b", 0)),
+    );
+    let (winner, loser) = match (a.unwrap(), b.unwrap()) {
+        (HTTPResponse::Success(winner), HTTPResponse::Failure(loser)) => (winner, loser),
+        (HTTPResponse::Failure(loser), HTTPResponse::Success(winner)) => (winner, loser),
+        r => panic!("expected exactly one append to succeed: {r:?}"),
+    };
+    assert_eq!(winner.offsets, 0..1);
+    assert_eq!(loser.code, ErrorCode::OffsetConflict);
+    assert_eq!(loser.end_offset, Some(1));
+
+    // the loser retries with the end offset returned
+    let r = testkit
+        .client
+        .append_log(make_request(name, "c", 1))
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 1..2 })");
+}
@@ -0,0 +1,77 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use morax_meta::CreateTopicRequest;
+use morax_meta::PostgresMetaService;
+use morax_protos::property::TopicProps;
+use morax_protos::request::ErrorCode;
+
+#[test]
+fn test_concurrent_create_if_not_exists() {
+    let Some(state) = tests_toolkit::make_test_env_state("test_concurrent_create_if_not_exists")
+    else {
+        return;
+    };
+
+    morax_runtime::test_runtime().block_on(async {
+        let meta = PostgresMetaService::new(&state.env_props.meta)
+            .await
+            .unwrap();
+        let request = CreateTopicRequest {
+            name: "log".to_string(),
+            properties: TopicProps {
+                storage: state.env_props.storage.clone(),
+                verify_writes: false,
+                max_split_bytes: None,
+                retention_ms: None,
+            },
+            if_not_exists: true,
+        };
+
+        let creates = (0..8).map(|_| meta.create_topic(request.clone()));
+        let topics = futures::future::join_all(creates).await;
+        let topic_ids = topics
+            .into_iter()
+            .map(|topic| topic.unwrap().id)
+            .collect::<Vec<_>>();
+        assert!(topic_ids.iter().all(|id| *id == topic_ids[0]));
+
+        let err = meta
+            .create_topic(CreateTopicRequest {
+                if_not_exists: false,
+                ..request.clone()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ErrorCode>().copied(),
+            Some(ErrorCode::AlreadyExists)
+        );
+
+        // re-creating with different properties is not masked
+        let mut properties = request.properties.clone();
+        properties.verify_writes = true;
+        let err = meta
+            .create_topic(CreateTopicRequest {
+                properties,
+                ..request
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ErrorCode>().copied(),
+            Some(ErrorCode::AlreadyExists)
+        );
+    });
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::DeleteLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use test_harness::test;
+
+#[test(harness)]
+async fn test_delete_log(testkit: Testkit) {
+    let name = "db_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "db_log" })"###);
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: name.clone(),
+            entries: vec![Entry {
+                index: None,
+                data: BASE64_STANDARD.encode("0"),
+            }],
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..1 })");
+
+    let r = testkit
+        .client
+        .delete_log(DeleteLogRequest { name: name.clone() })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(DeleteLogResponse { name: "db_log" })"###);
+
+    let r = testkit
+        .client
+        .delete_log(DeleteLogRequest { name: name.clone() })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::NotFound));
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name,
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(_)));
+}
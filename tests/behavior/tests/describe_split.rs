@@ -0,0 +1,102 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPClient;
+use morax_client::HTTPResponse;
+use morax_meta::FetchRecordBatchesRequest;
+use morax_meta::PostgresMetaService;
+use morax_protos::property::TopicProps;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+
+#[test]
+fn test_describe_split() {
+    let Some(state) = tests_toolkit::start_test_server("test_describe_split") else {
+        return;
+    };
+
+    morax_runtime::test_runtime().block_on(async {
+        let server_addr = format!("http://{}", state.server_state.broker_advertise_addr());
+        let client = HTTPClient::new(server_addr, reqwest::ClientBuilder::new()).unwrap();
+        let meta = PostgresMetaService::new(&state.env_props.meta)
+            .await
+            .unwrap();
+
+        let name = "described_log".to_string();
+        let r = client
+            .create_log(CreateLogRequest {
+                name: name.clone(),
+                properties: TopicProps {
+                    storage: state.env_props.storage.clone(),
+                    verify_writes: false,
+                    max_split_bytes: None,
+                    retention_ms: None,
+                },
+                if_not_exists: false,
+            })
+            .await
+            .unwrap();
+        assert!(matches!(r, HTTPResponse::Success(_)));
+
+        for data in [vec!["0"], vec!["1", "22"]] {
+            let entries = data
+                .into_iter()
+                .map(|data| Entry {
+                    index: None,
+                    data: BASE64_STANDARD.encode(data),
+                })
+                .collect();
+            let r = client
+                .append_log(AppendLogRequest {
+                    name: name.clone(),
+                    entries,
+                    expected_offset: None,
+                })
+                .await
+                .unwrap();
+            assert!(matches!(r, HTTPResponse::Success(_)));
+        }
+
+        let splits = meta
+            .fetch_record_batches(FetchRecordBatchesRequest {
+                topic_id: Default::default(),
+                topic_name: name.clone(),
+                offset: 1,
+            })
+            .await
+            .unwrap();
+        assert_eq!(splits.len(), 1);
+        let split_id = splits[0].split_id.clone();
+
+        let HTTPResponse::Success(r) = client.describe_split(&split_id).await.unwrap() else {
+            panic!("failed to describe split {split_id}");
+        };
+        assert_eq!(r.split_id, split_id);
+        assert_compact_debug_snapshot!(
+            (r.log_name, r.format, r.size_bytes, r.entry_count, r.offsets),
+            @r###"("described_log", "v1", 20, 2, 1..3)"###
+        );
+
+        let r = client.describe_split("absent_split").await.unwrap();
+        assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::NotFound));
+    });
+
+    state.server_state.shutdown();
+    morax_runtime::test_runtime().block_on(state.server_state.await_shutdown());
+}
@@ -0,0 +1,57 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use test_harness::test;
+
+#[test(harness)]
+async fn test_error_codes(testkit: Testkit) {
+    let request = CreateLogRequest {
+        name: "db_log".to_string(),
+        properties: testkit.topic_props.clone(),
+        if_not_exists: false,
+    };
+    let r = testkit.client.create_log(request.clone()).await.unwrap();
+    assert!(matches!(r, HTTPResponse::Success(_)));
+    let r = testkit.client.create_log(request).await.unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::AlreadyExists));
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: "absent_log".to_string(),
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::NotFound));
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: "absent_log".to_string(),
+            entries: vec![],
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::NotFound));
+}
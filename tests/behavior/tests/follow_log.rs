@@ -0,0 +1,78 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use futures::StreamExt;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::FollowLogRequest;
+use test_harness::test;
+
+fn make_entry(payload: &str) -> Entry {
+    Entry {
+        index: None,
+        data: BASE64_STANDARD.encode(payload),
+    }
+}
+
+#[test(harness)]
+async fn test_follow_log(testkit: Testkit) {
+    let name = "followed_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "followed_log" })"###);
+
+    let r = testkit
+        .client
+        .follow_log(FollowLogRequest {
+            name: name.clone(),
+            offset: 0,
+            timeout_ms: Some(10_000),
+        })
+        .await
+        .unwrap();
+    let HTTPResponse::Success(mut entries) = r else {
+        panic!("failed to follow log");
+    };
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: name.clone(),
+            entries: vec![make_entry("0"), make_entry("1")],
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..2 })");
+
+    let entry = entries.next().await.unwrap().unwrap();
+    assert_compact_debug_snapshot!(entry, @r###"Entry { index: Some(0), data: "MA==" }"###);
+    let entry = entries.next().await.unwrap().unwrap();
+    assert_compact_debug_snapshot!(entry, @r###"Entry { index: Some(1), data: "MQ==" }"###);
+}
@@ -0,0 +1,108 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::property::StorageProps;
+use morax_protos::property::TopicProps;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use opendal::services::FsConfig;
+use test_harness::test;
+
+fn make_entry(payload: &str) -> Entry {
+    Entry {
+        index: None,
+        data: BASE64_STANDARD.encode(payload),
+    }
+}
+
+#[test(harness)]
+async fn test_fs_storage(testkit: Testkit) {
+    let dir = tempfile::tempdir().unwrap();
+    let name = "fs_log".to_string();
+
+    let mut config = FsConfig::default();
+    config.root = Some(dir.path().to_string_lossy().to_string());
+    let properties = TopicProps {
+        storage: StorageProps::Fs(config),
+        verify_writes: true,
+        max_split_bytes: None,
+        retention_ms: None,
+    };
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties,
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "fs_log" })"###);
+
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: name.clone(),
+            entries: vec![make_entry("0"), make_entry("1")],
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..2 })");
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.clone(),
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(0), data: "MA==" }, Entry { index: Some(1), data: "MQ==" }], next_offset: 2, start_offset: 0, end_offset: 2 })"###);
+
+    let splits = std::fs::read_dir(dir.path().join("fs_log"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(splits.len(), 1);
+
+    // a split deleted out of band is reported as missing rather than as a raw read failure
+    std::fs::remove_file(&splits[0]).unwrap();
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name,
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    let split_id = splits[0].file_name().unwrap().to_string_lossy().to_string();
+    let HTTPResponse::Failure(resp) = r else {
+        panic!("expected a failure: {r:?}");
+    };
+    assert_eq!(resp.code, ErrorCode::DataLoss);
+    assert!(resp.message.contains(&split_id), "{}", resp.message);
+}
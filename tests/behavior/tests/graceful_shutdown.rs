@@ -0,0 +1,120 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPClient;
+use morax_protos::property::TopicProps;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use sqlx::Connection;
+use sqlx::PgConnection;
+
+async fn has_lock_waits(url: &str) -> bool {
+    let mut conn = PgConnection::connect(url).await.unwrap();
+    let waiting = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_locks l JOIN pg_database d ON l.database = d.oid \
+        WHERE NOT l.granted AND d.datname = current_database())",
+    )
+    .fetch_one(&mut conn)
+    .await
+    .unwrap();
+    conn.close().await.unwrap();
+    waiting
+}
+
+#[test]
+fn test_shutdown_report() {
+    let Some(state) = tests_toolkit::start_test_server("test_shutdown_report") else {
+        return;
+    };
+
+    let server_state = state.server_state;
+    let report = morax_runtime::test_runtime().block_on(async move {
+        let server_addr = format!("http://{}", server_state.broker_advertise_addr());
+        let builder = reqwest::ClientBuilder::new();
+        let client = Arc::new(HTTPClient::new(server_addr, builder).unwrap());
+        let name = "draining_log".to_string();
+
+        let r = client
+            .create_log(CreateLogRequest {
+                name: name.clone(),
+                properties: TopicProps {
+                    storage: state.env_props.storage.clone(),
+                    verify_writes: false,
+                    max_split_bytes: None,
+                    retention_ms: None,
+                },
+                if_not_exists: false,
+            })
+            .await
+            .unwrap();
+        assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "draining_log" })"###);
+
+        // hold the lock of the log offsets, so that the append below stays in flight at its commit
+        let url = state.env_props.meta.service_url.as_str();
+        let mut conn = PgConnection::connect(url).await.unwrap();
+        let mut txn = conn.begin().await.unwrap();
+        sqlx::query(
+            "SELECT last_offset FROM topic_offsets \
+            WHERE topic_id = (SELECT id FROM topics WHERE name = $1) FOR UPDATE",
+        )
+        .bind(&name)
+        .execute(&mut *txn)
+        .await
+        .unwrap();
+
+        let append = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .append_log(AppendLogRequest {
+                        name,
+                        entries: vec![Entry {
+                            index: None,
+                            data: BASE64_STANDARD.encode("0"),
+                        }],
+                        expected_offset: None,
+                    })
+                    .await
+                    .unwrap()
+            }
+        });
+        let mut blocked = false;
+        for _ in 0..100 {
+            blocked = has_lock_waits(url).await;
+            if blocked {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(blocked, "append is not in flight in time");
+
+        // the append in flight completes during the graceful shutdown
+        server_state.shutdown();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        txn.rollback().await.unwrap();
+        let r = append.await.unwrap();
+        assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..1 })");
+
+        server_state.await_shutdown().await
+    });
+
+    assert_compact_debug_snapshot!(report, @"ShutdownReport { broker: Some(DrainReport { requests_drained: 1, requests_completed: 1, splits_flushed: 0, forced_cancellations: 0 }) }");
+}
@@ -0,0 +1,60 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use morax_protos::config::FileAppenderConfig;
+use morax_protos::config::LogConfig;
+use morax_protos::config::LogFormat;
+use morax_protos::config::LogRotation;
+use morax_protos::config::TelemetryConfig;
+
+#[test]
+fn test_json_file_logs() {
+    let dir = tempfile::tempdir().unwrap();
+    let drop_guards = morax_telemetry::init(&TelemetryConfig {
+        log: LogConfig {
+            stderr: None,
+            file: Some(FileAppenderConfig {
+                filter: "INFO".to_string(),
+                dir: dir.path().to_string_lossy().to_string(),
+                name: "morax".to_string(),
+                format: LogFormat::Json,
+                rotation: LogRotation::Never,
+                max_files: None,
+            }),
+        },
+        traces: None,
+    });
+
+    log::info!(request_id = "r-1"; "structured log line");
+    // flush the buffered lines
+    drop(drop_guards);
+
+    let file = std::fs::read_dir(dir.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let content = std::fs::read_to_string(file.path()).unwrap();
+    // every line is a standalone JSON object
+    let lines = content
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let line = lines
+        .iter()
+        .find(|line| line["message"] == "structured log line")
+        .unwrap_or_else(|| panic!("log line not found in {content}"));
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["kvs"]["request_id"], "r-1");
+}
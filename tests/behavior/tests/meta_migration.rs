@@ -0,0 +1,74 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use morax_meta::PostgresMetaService;
+use morax_meta::LATEST_META_VERSION;
+use sqlx::Connection;
+use sqlx::PgConnection;
+
+async fn split_id_index_exists(conn: &mut PgConnection) -> bool {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_indexes WHERE indexname = 'topic_splits_split_id')",
+    )
+    .fetch_one(conn)
+    .await
+    .unwrap()
+}
+
+#[test]
+fn test_migrate_from_v1() {
+    let Some(state) = tests_toolkit::make_test_env_state("test_migrate_from_v1") else {
+        return;
+    };
+
+    morax_runtime::test_runtime().block_on(async {
+        let url = state.env_props.meta.service_url.as_str();
+        PostgresMetaService::new(&state.env_props.meta)
+            .await
+            .unwrap();
+
+        // roll the database back to a v1 schema
+        let mut conn = PgConnection::connect(url).await.unwrap();
+        sqlx::query("DROP INDEX topic_splits_split_id")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM meta_version WHERE version > 1")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        assert!(!split_id_index_exists(&mut conn).await);
+
+        PostgresMetaService::new(&state.env_props.meta)
+            .await
+            .unwrap();
+        assert!(split_id_index_exists(&mut conn).await);
+        let version: i32 = sqlx::query_scalar("SELECT MAX(version) FROM meta_version")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(version, LATEST_META_VERSION);
+
+        // migrations are idempotent
+        sqlx::query("DELETE FROM meta_version WHERE version > 1")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        PostgresMetaService::new(&state.env_props.meta)
+            .await
+            .unwrap();
+        assert!(split_id_index_exists(&mut conn).await);
+        conn.close().await.unwrap();
+    });
+}
@@ -40,6 +40,7 @@ async fn test_simple_pubsub(testkit: Testkit) {
         .create_log(CreateLogRequest {
             name: name.clone(),
             properties,
+            if_not_exists: false,
         })
         .await
         .unwrap();
@@ -50,6 +51,7 @@ async fn test_simple_pubsub(testkit: Testkit) {
         .append_log(AppendLogRequest {
             name: name.clone(),
             entries: vec![make_entry("0"), make_entry("1")],
+            expected_offset: None,
         })
         .await
         .unwrap();
@@ -57,8 +59,12 @@ async fn test_simple_pubsub(testkit: Testkit) {
 
     let r = testkit
         .client
-        .read_log(ReadLogRequest { name, offset: 0 })
+        .read_log(ReadLogRequest {
+            name,
+            offset: 0,
+            max_bytes: None,
+        })
         .await
         .unwrap();
-    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(0), data: "MA==" }, Entry { index: Some(1), data: "MQ==" }] })"###);
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(0), data: "MA==" }, Entry { index: Some(1), data: "MQ==" }], next_offset: 2, start_offset: 0, end_offset: 2 })"###);
 }
@@ -0,0 +1,99 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use test_harness::test;
+
+fn make_entry(payload: &str) -> Entry {
+    Entry {
+        index: None,
+        data: BASE64_STANDARD.encode(payload),
+    }
+}
+
+#[test(harness)]
+async fn test_read_pages(testkit: Testkit) {
+    let name = "db_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Success(_)));
+
+    // two splits: 0..1 and 1..3
+    for entries in [vec!["aaaa"], vec!["bbbb", "cccc"]] {
+        let r = testkit
+            .client
+            .append_log(AppendLogRequest {
+                name: name.clone(),
+                entries: entries.into_iter().map(make_entry).collect(),
+                expected_offset: None,
+            })
+            .await
+            .unwrap();
+        assert!(matches!(r, HTTPResponse::Success(_)));
+    }
+
+    let read_page = |offset| {
+        testkit.client.read_log(ReadLogRequest {
+            name: name.clone(),
+            offset,
+            max_bytes: NonZeroUsize::new(8),
+        })
+    };
+
+    // the first page spans both splits, and the end offset tells how far the tail of the log is
+    let r = read_page(0).await.unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(0), data: "YWFhYQ==" }, Entry { index: Some(1), data: "YmJiYg==" }], next_offset: 2, start_offset: 0, end_offset: 3 })"###);
+
+    let r = read_page(2).await.unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(2), data: "Y2NjYw==" }], next_offset: 3, start_offset: 0, end_offset: 3 })"###);
+
+    // reading at the end of the log returns no entries until more are appended
+    let r = read_page(3).await.unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(ReadLogResponse { entries: [], next_offset: 3, start_offset: 0, end_offset: 3 })");
+
+    let r = read_page(4).await.unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::OutOfRange));
+
+    // an entry larger than max_bytes is still returned alone
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.clone(),
+            offset: 1,
+            max_bytes: NonZeroUsize::new(1),
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(1), data: "YmJiYg==" }], next_offset: 2, start_offset: 0, end_offset: 3 })"###);
+}
@@ -0,0 +1,22 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use test_harness::test;
+
+#[test(harness)]
+async fn test_ready_check(testkit: Testkit) {
+    testkit.client.ready_check().await.unwrap();
+}
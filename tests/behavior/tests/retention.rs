@@ -0,0 +1,134 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::property::TopicProps;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use test_harness::test;
+
+fn make_entries(data: &str) -> Vec<Entry> {
+    vec![Entry {
+        index: None,
+        data: BASE64_STANDARD.encode(data),
+    }]
+}
+
+#[test(harness)]
+async fn test_retention(testkit: Testkit) {
+    let name = "expiring_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: TopicProps {
+                retention_ms: NonZeroU64::new(500),
+                ..testkit.topic_props.clone()
+            },
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "expiring_log" })"###);
+
+    for data in ["0", "1"] {
+        let r = testkit
+            .client
+            .append_log(AppendLogRequest {
+                name: name.clone(),
+                entries: make_entries(data),
+                expected_offset: None,
+            })
+            .await
+            .unwrap();
+        assert!(matches!(r, HTTPResponse::Success(_)));
+    }
+
+    let mut expired = false;
+    for _ in 0..100 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let r = testkit
+            .client
+            .read_log(ReadLogRequest {
+                name: name.clone(),
+                offset: 0,
+                max_bytes: None,
+            })
+            .await
+            .unwrap();
+        if matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::OutOfRange) {
+            expired = true;
+            break;
+        }
+    }
+    assert!(expired, "splits are not expired in time");
+
+    // the start offset reported to readers moves past the expired splits
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.clone(),
+            offset: 2,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(ReadLogResponse { entries: [], next_offset: 2, start_offset: 2, end_offset: 2 })");
+
+    // offsets keep growing after the splits before are expired
+    let r = testkit
+        .client
+        .append_log(AppendLogRequest {
+            name: name.clone(),
+            entries: make_entries("2"),
+            expected_offset: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 2..3 })");
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.clone(),
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::OutOfRange));
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name,
+            offset: 2,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(2), data: "Mg==" }], next_offset: 3, start_offset: 2, end_offset: 3 })"###);
+}
@@ -0,0 +1,95 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use test_harness::test;
+
+fn make_request(name: &str, payload: &str) -> AppendLogRequest {
+    AppendLogRequest {
+        name: name.to_string(),
+        entries: vec![Entry {
+            index: None,
+            data: BASE64_STANDARD.encode(payload),
+        }],
+        expected_offset: None,
+    }
+}
+
+#[test(harness)]
+async fn test_seal_log(testkit: Testkit) {
+    let name = "db_log";
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.to_string(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Success(_)));
+
+    let r = testkit
+        .client
+        .append_log(make_request(name, "0"))
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 0..1 })");
+
+    let r = testkit.client.seal_log(name).await.unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(SealLogResponse { name: "db_log", sealed: true })"###);
+
+    let r = testkit
+        .client
+        .append_log(make_request(name, "1"))
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::Sealed));
+
+    // a sealed log can still be read
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.to_string(),
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(0), data: "MA==" }], next_offset: 1, start_offset: 0, end_offset: 1 })"###);
+
+    let r = testkit.client.unseal_log(name).await.unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(SealLogResponse { name: "db_log", sealed: false })"###);
+
+    let r = testkit
+        .client
+        .append_log(make_request(name, "1"))
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @"Success(AppendLogResponse { offsets: 1..2 })");
+
+    let r = testkit.client.seal_log("absent_log").await.unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::NotFound));
+}
@@ -0,0 +1,89 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use morax_client::HTTPClient;
+use morax_client::HTTPResponse;
+use morax_protos::config::StorageFault;
+use morax_protos::config::StorageFaultKind;
+use morax_protos::config::StorageOperation;
+use morax_protos::property::StorageProps;
+use morax_protos::property::TopicProps;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use opendal::services::FsConfig;
+
+#[test]
+fn test_fail_appends_on_storage_faults() {
+    let faults = vec![StorageFault {
+        operation: StorageOperation::Write,
+        kind: StorageFaultKind::Unexpected,
+        ratio: 1.0,
+    }];
+    let Some(state) =
+        tests_toolkit::start_faulty_test_server("test_fail_appends_on_storage_faults", faults)
+    else {
+        return;
+    };
+
+    let server_state = state.server_state;
+    morax_runtime::test_runtime().block_on(async move {
+        let server_addr = format!("http://{}", server_state.broker_advertise_addr());
+        let builder = reqwest::ClientBuilder::new();
+        let client = HTTPClient::new(server_addr, builder).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let name = "faulty_log".to_string();
+
+        let mut config = FsConfig::default();
+        config.root = Some(dir.path().to_string_lossy().to_string());
+        client
+            .create_log(CreateLogRequest {
+                name: name.clone(),
+                properties: TopicProps {
+                    storage: StorageProps::Fs(config),
+                    verify_writes: false,
+                    max_split_bytes: None,
+                    retention_ms: None,
+                },
+                if_not_exists: false,
+            })
+            .await
+            .unwrap();
+
+        let r = client
+            .append_log(AppendLogRequest {
+                name,
+                entries: vec![Entry {
+                    index: None,
+                    data: BASE64_STANDARD.encode("0"),
+                }],
+                expected_offset: None,
+            })
+            .await
+            .unwrap();
+        let HTTPResponse::Failure(resp) = r else {
+            panic!("expected a failure: {r:?}");
+        };
+        assert_eq!(resp.code, ErrorCode::Unexpected);
+
+        // the split is never written, so there is nothing to commit or to clean up
+        assert!(!dir.path().join("faulty_log").exists());
+
+        server_state.shutdown();
+        server_state.await_shutdown().await;
+    });
+}
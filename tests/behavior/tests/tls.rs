@@ -0,0 +1,82 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPClient;
+use morax_protos::config::TlsConfig;
+use morax_protos::property::TopicProps;
+use morax_protos::request::CreateLogRequest;
+
+fn testdata(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata")
+        .join("tls")
+        .join(name)
+}
+
+#[test]
+fn test_tls_listener() {
+    let tls = TlsConfig {
+        cert_path: testdata("server.pem").to_string_lossy().to_string(),
+        key_path: testdata("server.key").to_string_lossy().to_string(),
+        client_ca_path: None,
+    };
+    let Some(state) = tests_toolkit::start_tls_test_server("test_tls_listener", tls) else {
+        return;
+    };
+
+    morax_runtime::test_runtime().block_on(async {
+        let server_addr = state.server_state.broker_advertise_addr();
+        let properties = TopicProps {
+            storage: state.env_props.storage.clone(),
+            verify_writes: false,
+            max_split_bytes: None,
+            retention_ms: None,
+        };
+
+        // the test certificate is issued for localhost
+        let ca = std::fs::read(testdata("ca.pem")).unwrap();
+        let builder = reqwest::ClientBuilder::new()
+            .add_root_certificate(reqwest::Certificate::from_pem(&ca).unwrap())
+            .resolve("localhost", server_addr);
+        let endpoint = format!("https://localhost:{}", server_addr.port());
+        let client = HTTPClient::new(endpoint, builder).unwrap();
+        let r = client
+            .create_log(CreateLogRequest {
+                name: "tls_log".to_string(),
+                properties: properties.clone(),
+                if_not_exists: false,
+            })
+            .await
+            .unwrap();
+        assert_compact_debug_snapshot!(r, @r###"Success(CreateLogResponse { name: "tls_log" })"###);
+
+        // plaintext requests are rejected by the TLS listener
+        let builder = reqwest::ClientBuilder::new();
+        let client = HTTPClient::new(format!("http://{server_addr}"), builder).unwrap();
+        let r = client
+            .create_log(CreateLogRequest {
+                name: "plaintext_log".to_string(),
+                properties,
+                if_not_exists: false,
+            })
+            .await;
+        assert!(r.is_err());
+    });
+
+    state.server_state.shutdown();
+    morax_runtime::test_runtime().block_on(state.server_state.await_shutdown());
+}
@@ -0,0 +1,118 @@
+// Copyright 2024 tison <wander4096@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use behavior_tests::harness;
+use behavior_tests::Testkit;
+use insta::assert_compact_debug_snapshot;
+use morax_client::HTTPResponse;
+use morax_protos::request::AppendLogRequest;
+use morax_protos::request::CreateLogRequest;
+use morax_protos::request::Entry;
+use morax_protos::request::ErrorCode;
+use morax_protos::request::ReadLogRequest;
+use morax_protos::request::TrimLogRequest;
+use test_harness::test;
+
+#[test(harness)]
+async fn test_trim_log(testkit: Testkit) {
+    let name = "db_log".to_string();
+
+    let r = testkit
+        .client
+        .create_log(CreateLogRequest {
+            name: name.clone(),
+            properties: testkit.topic_props.clone(),
+            if_not_exists: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Success(_)));
+
+    // three splits: 0..2, 2..4 and 4..6
+    for i in 0..3 {
+        let r = testkit
+            .client
+            .append_log(AppendLogRequest {
+                name: name.clone(),
+                entries: vec![
+                    Entry {
+                        index: None,
+                        data: BASE64_STANDARD.encode(format!("{}", i * 2)),
+                    },
+                    Entry {
+                        index: None,
+                        data: BASE64_STANDARD.encode(format!("{}", i * 2 + 1)),
+                    },
+                ],
+                expected_offset: None,
+            })
+            .await
+            .unwrap();
+        assert!(matches!(r, HTTPResponse::Success(_)));
+    }
+
+    // the split 2..4 straddles the start offset and is kept
+    let r = testkit
+        .client
+        .trim_log(TrimLogRequest {
+            name: name.clone(),
+            offset: 3,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(TrimLogResponse { name: "db_log", start_offset: 3 })"###);
+
+    // the start offset never moves backwards
+    let r = testkit
+        .client
+        .trim_log(TrimLogRequest {
+            name: name.clone(),
+            offset: 1,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(TrimLogResponse { name: "db_log", start_offset: 3 })"###);
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.clone(),
+            offset: 0,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::OutOfRange));
+
+    let r = testkit
+        .client
+        .read_log(ReadLogRequest {
+            name: name.clone(),
+            offset: 3,
+            max_bytes: None,
+        })
+        .await
+        .unwrap();
+    assert_compact_debug_snapshot!(r, @r###"Success(ReadLogResponse { entries: [Entry { index: Some(3), data: "Mw==" }, Entry { index: Some(4), data: "NA==" }, Entry { index: Some(5), data: "NQ==" }], next_offset: 6, start_offset: 3, end_offset: 6 })"###);
+
+    // trimming beyond the end of the log is rejected
+    let r = testkit
+        .client
+        .trim_log(TrimLogRequest { name, offset: 7 })
+        .await
+        .unwrap();
+    assert!(matches!(r, HTTPResponse::Failure(resp) if resp.code == ErrorCode::OutOfRange));
+}
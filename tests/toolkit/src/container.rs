@@ -64,6 +64,12 @@ fn make_meta_service_config(container: &Container<Postgres>) -> MetaServiceConfi
     let port = container.get_host_port_ipv4(5432).unwrap();
     MetaServiceConfig {
         service_url: format!("postgres://{USERNAME}:{PASSWORD}@{host}:{port}"),
+        commit_retry_max_times: None,
+        max_connections: None,
+        min_connections: None,
+        acquire_timeout_ms: None,
+        idle_timeout_ms: None,
+        faults: vec![],
     }
 }
 
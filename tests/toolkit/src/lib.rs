@@ -15,7 +15,9 @@
 use std::any::Any;
 
 pub use state::make_test_env_state;
+pub use state::start_faulty_test_server;
 pub use state::start_test_server;
+pub use state::start_tls_test_server;
 pub use state::TestEnvProps;
 pub use state::TestEnvState;
 
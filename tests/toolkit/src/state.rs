@@ -17,6 +17,9 @@ use std::net::SocketAddr;
 use morax_protos::config::BrokerConfig;
 use morax_protos::config::MetaServiceConfig;
 use morax_protos::config::ServerConfig;
+use morax_protos::config::StorageConfig;
+use morax_protos::config::StorageFault;
+use morax_protos::config::TlsConfig;
 use morax_protos::property::StorageProps;
 use morax_server::ServerState;
 use opendal::Operator;
@@ -65,6 +68,27 @@ pub(crate) fn read_test_env_props() -> Option<TestEnvProps> {
 }
 
 pub fn start_test_server(test_name: &str) -> Option<TestServerState> {
+    do_start_test_server(test_name, None, vec![])
+}
+
+pub fn start_tls_test_server(test_name: &str, tls: TlsConfig) -> Option<TestServerState> {
+    do_start_test_server(test_name, Some(tls), vec![])
+}
+
+/// Starts a test server whose storage operations fail or get corrupted by `faults`; the faults
+/// take effect only if morax-storage is built with the `fault-injection` feature.
+pub fn start_faulty_test_server(
+    test_name: &str,
+    faults: Vec<StorageFault>,
+) -> Option<TestServerState> {
+    do_start_test_server(test_name, None, faults)
+}
+
+fn do_start_test_server(
+    test_name: &str,
+    tls: Option<TlsConfig>,
+    faults: Vec<StorageFault>,
+) -> Option<TestServerState> {
     let TestEnvState {
         env_props,
         _drop_guards,
@@ -73,11 +97,20 @@ pub fn start_test_server(test_name: &str) -> Option<TestServerState> {
     let broker = BrokerConfig {
         listen_addr: SocketAddr::new(host, 0).to_string(),
         advertise_addr: None,
+        max_append_entries: None,
+        max_inflight_requests: None,
+        tls,
     };
     let server_state = morax_runtime::test_runtime()
         .block_on(morax_server::start(ServerConfig {
             broker,
             meta: env_props.meta.clone(),
+            storage: StorageConfig {
+                // expire splits promptly in tests of retention
+                retention_check_interval_ms: Some(100),
+                faults,
+                ..StorageConfig::default()
+            },
         }))
         .unwrap();
     Some(TestServerState {
@@ -114,20 +147,33 @@ pub fn make_test_env_state(test_name: &str) -> Option<TestEnvState> {
         },
     )));
 
-    match props.storage {
+    let client = match props.storage {
         StorageProps::S3(ref mut config) => {
             config.root = Some(format!("/{test_name}/"));
-            let client = Operator::from_config(config.clone()).unwrap().finish();
-            morax_runtime::test_runtime().block_on(async {
-                client.remove_all("/").await.unwrap();
-            });
-            _drop_guards.push(Box::new(scopeguard::guard_on_success((), move |()| {
-                morax_runtime::test_runtime().block_on(async move {
-                    client.remove_all("/").await.unwrap();
-                });
-            })));
+            Operator::from_config(config.clone()).unwrap().finish()
         }
-    }
+        StorageProps::Fs(ref mut config) => {
+            let root = config.root.as_deref().unwrap_or(".");
+            config.root = Some(format!("{root}/{test_name}/"));
+            Operator::from_config(config.clone()).unwrap().finish()
+        }
+        StorageProps::Gcs(ref mut config) => {
+            config.root = Some(format!("/{test_name}/"));
+            Operator::from_config(config.clone()).unwrap().finish()
+        }
+        StorageProps::Azblob(ref mut config) => {
+            config.root = Some(format!("/{test_name}/"));
+            Operator::from_config(config.clone()).unwrap().finish()
+        }
+    };
+    morax_runtime::test_runtime().block_on(async {
+        client.remove_all("/").await.unwrap();
+    });
+    _drop_guards.push(Box::new(scopeguard::guard_on_success((), move |()| {
+        morax_runtime::test_runtime().block_on(async move {
+            client.remove_all("/").await.unwrap();
+        });
+    })));
 
     // ensure containers get dropped last
     _drop_guards.reverse();